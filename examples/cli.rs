@@ -36,7 +36,7 @@ fn main() {
         let mut out_file =
             fs::File::create(file_path).expect("An Error Occured while creating file");
         out_file
-            .write_all(format!("{:?}", res).as_bytes())
+            .write_all(format!("{}", res).as_bytes())
             .expect("An Error Occured while writing data to the output file!");
     }
 }
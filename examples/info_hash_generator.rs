@@ -1,7 +1,6 @@
-use bencode_parser::{BEncode, Options};
+use bencode_parser::{BEncode, Options, Spanned, SpannedBEncode};
 use clap::Parser;
 use sha1::{Digest, Sha1};
-use std::convert::TryInto;
 use std::fs;
 use std::path::PathBuf;
 
@@ -25,18 +24,19 @@ fn main() {
     let path: PathBuf = PathBuf::from(&args.input);
     let bytes = fs::read(path).expect("Couldn't Read File!");
 
-    let options: Options = Options { parse_hex: false };
-    let res: BEncode = BEncode::parse(bytes, options);
+    let options: Options = Options::default();
+    let res: Spanned<SpannedBEncode> =
+        BEncode::parse_spanned(&bytes, options).expect("Invalid bencode!");
 
-    if let BEncode::Dictionary(dict) = res {
-        let info: &BEncode = dict.get("info").expect("Couldn't find Info object!");
-        let encoded: String = BEncode::encode(info);
+    if let Some(info) = res.value.get("info") {
+        // Hash the original bytes of the info dictionary, so non-canonical torrents get the right hash
+        let encoded: &[u8] = info.raw(&bytes).expect("The span comes from these bytes");
 
         let mut hasher = Sha1::new();
-        hasher.update(encoded.as_bytes());
+        hasher.update(encoded);
         let result = hasher.finalize();
 
-        let hash_bytes: [u8; 20] = result.try_into().unwrap();
+        let hash_bytes: [u8; 20] = result.into();
         let info_hash = hex::encode(hash_bytes);
         println!("Info Hash: {info_hash}");
     }
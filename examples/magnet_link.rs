@@ -1,7 +1,6 @@
-use bencode_parser::{BEncode, Options};
+use bencode_parser::{BEncode, Options, Spanned, SpannedBEncode};
 use clap::Parser;
 use sha1::{Digest, Sha1};
-use std::convert::TryInto;
 use std::fs;
 use std::path::PathBuf;
 
@@ -29,74 +28,61 @@ fn main() {
     let path: PathBuf = PathBuf::from(&args.input);
     let bytes = fs::read(path).expect("Couldn't Read File!");
 
-    let options: Options = Options { parse_hex: false };
-    let res: BEncode = BEncode::parse(bytes, options);
-
-    if let BEncode::Dictionary(dict) = res {
-        let mut magnet: String = String::from("magnet:?xt=urn:btih:");
-
-        let info: &BEncode = dict.get("info").expect("Couldn't find Info object!");
-        let info_hash: String = get_info_hash(info);
-        magnet.push_str(&info_hash);
-
-        // ========================== GET DISPLAY NAME ======================================
-        if let BEncode::Dictionary(info_dict) = info {
-            let name_benc: BEncode = BEncode::String(String::new());
-            let name_obj: &BEncode = info_dict.get("name").unwrap_or(&name_benc);
-
-            if let BEncode::String(name) = name_obj {
-                let display_name: String = format!("&dn={}", name.clone().replace(' ', "+"));
-                magnet.push_str(&display_name);
-                // println!("{magnet}");
-            }
-        }
-        // ==================================================================================
-
-        // ========================== GET TRACKERS LIST =====================================
-        let announce_list_benc: &BEncode = dict.get("announce-list").expect("No Trackers Found!");
-        if let BEncode::List(announce_list) = announce_list_benc {
-            for list in announce_list {
-                if let BEncode::List(l) = list {
-                    if let BEncode::String(tracker) = &l[0] {
-                        let tracker_string: String = format!(
-                            "&tr={}",
-                            tracker.clone().replace(':', "%3A").replace('/', "%2F")
-                        );
-                        magnet.push_str(&tracker_string);
-                    }
-                }
-            }
-        }
-        // ==================================================================================
-
-        // ========================== GET WEB SEEDS =========================================
-        let dummy_list: BEncode = BEncode::List(vec![]);
-        let urls_benc: &BEncode = dict.get("url-list").unwrap_or(&dummy_list);
-
-        if let BEncode::List(urls) = urls_benc {
-            for link_benc in urls {
-                if let BEncode::String(url) = link_benc {
-                    let web_string: String = format!(
-                        "&ws={}",
-                        url.clone().replace(':', "%3A").replace('/', "%2F")
-                    );
-                    magnet.push_str(&web_string);
-                }
-            }
-        }
-        // ==================================================================================
-
-        println!("{magnet}");
+    let options: Options = Options::default();
+    let spanned: Spanned<SpannedBEncode> =
+        BEncode::parse_spanned(&bytes, options).expect("Invalid bencode!");
+    let info_hash: String = get_info_hash(
+        spanned
+            .value
+            .get("info")
+            .expect("Couldn't find Info object!")
+            .raw(&bytes)
+            .expect("The span comes from these bytes"),
+    );
+    let res: BEncode = spanned.value.into_bencode();
+
+    let mut magnet: String = format!("magnet:?xt=urn:btih:{}", info_hash);
+
+    // ========================== GET DISPLAY NAME ======================================
+    if let Some(name) = res.pointer("/info/name").and_then(BEncode::as_str) {
+        magnet.push_str(&format!("&dn={}", name.replace(' ', "+")));
+    }
+    // ==================================================================================
+
+    // ========================== GET TRACKERS LIST =====================================
+    let trackers = res
+        .query("announce-list[*][0]")
+        .expect("Valid query")
+        .filter_map(BEncode::as_str);
+    for tracker in trackers {
+        magnet.push_str(&format!("&tr={}", encode_url(tracker)));
     }
+    // ==================================================================================
+
+    // ========================== GET WEB SEEDS =========================================
+    let urls = res
+        .query("url-list[*]")
+        .expect("Valid query")
+        .filter_map(BEncode::as_str);
+    for url in urls {
+        magnet.push_str(&format!("&ws={}", encode_url(url)));
+    }
+    // ==================================================================================
+
+    println!("{magnet}");
 }
 
-fn get_info_hash(info: &BEncode) -> String {
-    let encoded: String = BEncode::encode(info);
+/// Escapes the characters of a URL that cannot appear in a magnet link parameter
+fn encode_url(url: &str) -> String {
+    url.replace(':', "%3A").replace('/', "%2F")
+}
 
+/// Hashes the original bytes of the info dictionary
+fn get_info_hash(info_bytes: &[u8]) -> String {
     let mut hasher = Sha1::new();
-    hasher.update(encoded.as_bytes());
+    hasher.update(info_bytes);
     let result = hasher.finalize();
 
-    let hash_bytes: [u8; 20] = result.try_into().unwrap();
+    let hash_bytes: [u8; 20] = result.into();
     hex::encode(hash_bytes)
 }
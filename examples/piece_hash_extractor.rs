@@ -1,5 +1,6 @@
-use bencode_parser::{BEncode, Options};
+use bencode_parser::{BEncode, Options, StringPolicy};
 use clap::Parser;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -10,8 +11,8 @@ struct Args {
     #[arg(short, long, default_value_t = String::from("./examples/big-buck-bunny.torrent"))]
     input: String,
 
-    /// Length of each hash. Default is 40 instead of 20 since coverting binary to hex returns twice as many characters
-    #[arg(short, long, default_value_t = 40)]
+    /// Length of each hash in bytes. Default is 20, the size of a SHA-1 hash
+    #[arg(short, long, default_value_t = 20)]
     length: usize,
 }
 
@@ -21,34 +22,24 @@ fn main() {
     let path: PathBuf = PathBuf::from(args.input);
     let bytes: Vec<u8> = fs::read(path).expect("Cannot read File!");
 
-    let options: Options = Options::default();
+    // `pieces` is kept binary even if it happens to be valid UTF-8
+    let options: Options = Options {
+        key_policies: BTreeMap::from([(b"pieces".to_vec(), StringPolicy::Bytes)]),
+        ..Options::default()
+    };
     let res: BEncode = BEncode::parse(bytes, options);
 
-    if let BEncode::Dictionary(obj) = res {
-        let info: &BEncode = obj
-            .get("info")
-            .expect("Cannot find Info object in the torrent");
-
-        if let BEncode::Dictionary(info_obj) = info {
-            let pieces: &BEncode = info_obj.get("pieces").expect("Cannot find Pieces!");
-            if let BEncode::String(str) = pieces {
-                if str.len() % args.length != 0 {
-                    panic!(
-                        "Pieces Hash not valid! (The length is not a multiple of {})",
-                        args.length
-                    );
-                }
-                let mut hashes: Vec<String> = Vec::new();
-                let mut start_idx: usize = 0;
-
-                while (start_idx + args.length) <= str.len() {
-                    let hash: String = str[start_idx..start_idx + args.length].to_string();
-                    hashes.push(hash);
-                    start_idx += args.length;
-                }
-
-                println!("{:?}", hashes);
-            }
-        }
+    let pieces: &[u8] = res
+        .get("info")
+        .and_then(|info| info.get("pieces")?.as_bytes())
+        .expect("Cannot find Pieces!");
+    if !pieces.len().is_multiple_of(args.length) {
+        panic!(
+            "Pieces Hash not valid! (The length is not a multiple of {})",
+            args.length
+        );
     }
+
+    let hashes: Vec<String> = pieces.chunks(args.length).map(hex::encode).collect();
+    println!("{:?}", hashes);
 }
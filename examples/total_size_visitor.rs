@@ -0,0 +1,47 @@
+use bencode_parser::{BEncode, Options, Visitor};
+use clap::Parser;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Path to the input file
+    #[arg(short, long, default_value_t = String::from("./examples/big-buck-bunny.torrent"))]
+    input: String,
+}
+
+/// Adds up every `length` field in the torrent without building a `BEncode` tree
+#[derive(Default)]
+struct LengthCounter {
+    next_is_length: bool,
+    files: usize,
+    total: i64,
+}
+
+impl<'a> Visitor<'a> for LengthCounter {
+    fn on_key(&mut self, key: &'a [u8]) {
+        self.next_is_length = key == b"length";
+    }
+
+    fn on_int(&mut self, value: i64) {
+        if self.next_is_length {
+            self.files += 1;
+            self.total += value;
+        }
+        self.next_is_length = false;
+    }
+}
+
+fn main() {
+    let args: Args = Args::parse();
+
+    let path: PathBuf = PathBuf::from(args.input);
+    let bytes: Vec<u8> = fs::read(path).expect("Cannot read File!");
+
+    let mut counter: LengthCounter = LengthCounter::default();
+    BEncode::visit(&bytes, Options::default(), &mut counter).expect("Invalid bencode!");
+
+    println!("Files: {}", counter.files);
+    println!("Total Size: {} bytes", counter.total);
+}
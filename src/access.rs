@@ -0,0 +1,117 @@
+//! Typed accessors that look inside a [`BEncode`] object without matching on its variants
+use crate::BEncode;
+use std::collections::BTreeMap;
+use std::ops::Index;
+
+impl BEncode {
+    /// Returns the number if this is a [`BEncode::Int`]
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Self::Int(num) => Some(*num),
+            _ => None,
+        }
+    }
+
+    /// Returns the text if this is a [`BEncode::String`], or a [`BEncode::BinaryStr`] that happens to be valid UTF-8
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            Self::BinaryStr(bin) => std::str::from_utf8(bin).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the contents if this is a [`BEncode::String`] or [`BEncode::BinaryStr`].
+    /// Note that a string decoded with [`crate::StringPolicy::Hex`] or [`crate::StringPolicy::LossyUtf8`] returns its text, not the original bytes
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::String(string) => Some(string.as_bytes()),
+            Self::BinaryStr(bin) => Some(bin),
+            _ => None,
+        }
+    }
+
+    /// Returns the items if this is a [`BEncode::List`]
+    pub fn as_list(&self) -> Option<&[BEncode]> {
+        match self {
+            Self::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if this is a [`BEncode::Dictionary`]
+    pub fn as_dict(&self) -> Option<&BTreeMap<Vec<u8>, BEncode>> {
+        match self {
+            Self::Dictionary(dict) => Some(dict),
+            _ => None,
+        }
+    }
+
+    /// Returns the item at `idx` if this is a [`BEncode::List`] long enough
+    pub fn index(&self, idx: usize) -> Option<&BEncode> {
+        self.as_list()?.get(idx)
+    }
+
+    /// Internal function that names the type of this object, for [`crate::TypeError`]
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Self::Int(_) | Self::BigInt(_) => "integer",
+            Self::String(_) => "string",
+            Self::BinaryStr(_) => "binary string",
+            Self::List(_) => "list",
+            Self::Dictionary(_) => "dictionary",
+            Self::Raw(_) => "raw value",
+        }
+    }
+
+    /// Returns true if this is a [`BEncode::Int`] or [`BEncode::BigInt`]
+    pub fn is_int(&self) -> bool {
+        matches!(self, Self::Int(_) | Self::BigInt(_))
+    }
+
+    /// Returns true if [`BEncode::as_str`] would return some text
+    pub fn is_str(&self) -> bool {
+        self.as_str().is_some()
+    }
+
+    /// Returns true if this is a [`BEncode::String`] or [`BEncode::BinaryStr`]
+    pub fn is_bytes(&self) -> bool {
+        matches!(self, Self::String(_) | Self::BinaryStr(_))
+    }
+
+    /// Returns true if this is a [`BEncode::List`]
+    pub fn is_list(&self) -> bool {
+        matches!(self, Self::List(_))
+    }
+
+    /// Returns true if this is a [`BEncode::Dictionary`]
+    pub fn is_dict(&self) -> bool {
+        matches!(self, Self::Dictionary(_))
+    }
+}
+
+/// Looks up a dictionary key, like `torrent["info"]["name"]`.
+/// Panics if this is not a [`BEncode::Dictionary`] or the key is missing, use [`BEncode::get`] to handle that instead
+impl Index<&str> for BEncode {
+    type Output = BEncode;
+
+    fn index(&self, key: &str) -> &BEncode {
+        match self.get(key) {
+            Some(value) => value,
+            None => panic!("No value under key {:?}", key),
+        }
+    }
+}
+
+/// Looks up a list item, like `torrent["announce-list"][0]`.
+/// Panics if this is not a [`BEncode::List`] or it is too short, use [`BEncode::index`] to handle that instead
+impl Index<usize> for BEncode {
+    type Output = BEncode;
+
+    fn index(&self, idx: usize) -> &BEncode {
+        match BEncode::index(self, idx) {
+            Some(value) => value,
+            None => panic!("No item at index {}", idx),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! The `BigInt` struct holds the checked decimal digits of an Integer that does not fit in an `i64`
+use crate::DecodeError;
+use std::fmt;
+use std::str::FromStr;
+
+/// The decimal digits of an Integer, with an optional leading `-`, checked so that they always encode to valid bencode.
+/// Leading zeros are kept as they were written, [`crate::BEncode::canonicalize`] removes them.
+/// The digits are a `String` in [`crate::BEncode`] and borrow from the input in [`crate::BEncodeRef`]
+///
+/// ## Example
+/// ```rust
+/// use bencode_parser::{BEncode, BigInt, DecodeError};
+///
+/// let big: BigInt = "123456789012345678901234567890".parse().unwrap();
+/// assert_eq!(BEncode::BigInt(big).to_bytes(), b"i123456789012345678901234567890e");
+/// assert_eq!(BigInt::new("-0042").unwrap().as_str(), "-0042");
+/// assert_eq!(BigInt::new("abc").unwrap_err(), DecodeError::InvalidInteger { offset: 0 });
+/// assert_eq!(BigInt::new("12-3").unwrap_err(), DecodeError::InvalidInteger { offset: 2 });
+/// assert!(BigInt::new("-").is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BigInt<S = String>(S);
+
+impl<S: AsRef<str>> BigInt<S> {
+    /// Checks that `digits` holds an optional `-` followed by at least one ASCII digit.
+    /// The offset of the error is the position of the first byte that is not allowed
+    pub fn new(digits: S) -> Result<Self, DecodeError> {
+        let text: &str = digits.as_ref();
+        let start: usize = text.starts_with('-') as usize;
+        if let Some(pos) = text[start..].bytes().position(|b| !b.is_ascii_digit()) {
+            return Err(DecodeError::InvalidInteger {
+                offset: start + pos,
+            });
+        }
+        if text.len() == start {
+            return Err(DecodeError::InvalidInteger { offset: start });
+        }
+        Ok(Self(digits))
+    }
+
+    /// Internal function that wraps digits that are already known to be valid
+    pub(crate) fn from_valid(digits: S) -> Self {
+        Self(digits)
+    }
+
+    /// Returns the digits, with the leading `-` if the number is negative
+    pub fn as_str(&self) -> &str {
+        self.0.as_ref()
+    }
+
+    /// Copies the digits if they are borrowed
+    pub fn into_owned(self) -> BigInt {
+        BigInt(self.as_str().to_string())
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = DecodeError;
+
+    fn from_str(digits: &str) -> Result<Self, DecodeError> {
+        Self::new(digits.to_string())
+    }
+}
+
+impl<S: AsRef<str>> fmt::Display for BigInt<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,93 @@
+//! The `BEncodeRef` enum is a zero-copy view of a bencode value, borrowing its ByteStrings from the input buffer
+use crate::parser::{self, ParseNode};
+use crate::{BEncode, BigInt, DecodeError, Options, StringPolicy};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// The borrowed BEncode Object.
+/// This enum mirrors [`BEncode`], but every ByteString and dictionary key is a slice into the parsed input, so nothing is copied while parsing.
+/// Use [`BEncodeRef::to_owned`] to turn it into a [`BEncode`]
+#[derive(Debug, Clone)]
+pub enum BEncodeRef<'a> {
+    /// The `Int` variant holds the integers parsed from bencode
+    Int(i64),
+    /// The `BigInt` variant holds the decimal digits of integers that do not fit in an `i64`, see [`Options::big_ints`]
+    BigInt(BigInt<&'a str>),
+    /// The `Bytes` variant holds the raw contents of a bencode ByteString, whether or not it is valid UTF-8
+    Bytes(&'a [u8]),
+    /// The `List` variant holds parsed bencode Lists
+    List(Vec<BEncodeRef<'a>>),
+    /// The `Dictionary` variant holds parsed bencode Dictionaries, keyed by the raw bytes of each key
+    Dictionary(BTreeMap<&'a [u8], BEncodeRef<'a>>),
+}
+
+impl<'a> BEncodeRef<'a> {
+    /// This function returns the borrowed [`BEncodeRef`] object, or a [`DecodeError`] describing the first problem found in the input.
+    /// [`Options::string_policy`] and [`Options::key_policies`] have no effect here since ByteStrings are never decoded
+    pub fn try_parse(bytes: &'a [u8], options: Options) -> Result<Self, DecodeError> {
+        let (root, idx) = parser::parse_value(bytes, &options)?;
+        if idx < bytes.len() {
+            return Err(DecodeError::TrailingData { offset: idx });
+        }
+        Ok(root)
+    }
+
+    /// Returns the value stored under `key` if this is a [`BEncodeRef::Dictionary`]
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<&BEncodeRef<'a>> {
+        match self {
+            Self::Dictionary(dict) => dict.get(key.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Copies this object into an owned [`BEncode`].
+    /// ByteStrings that are valid UTF-8 become [`BEncode::String`] and the rest become [`BEncode::BinaryStr`]
+    pub fn to_owned(&self) -> BEncode {
+        match self {
+            Self::Int(num) => BEncode::Int(*num),
+            Self::BigInt(digits) => BEncode::BigInt(digits.clone().into_owned()),
+            Self::Bytes(bytes) => match std::str::from_utf8(bytes) {
+                Ok(out_str) => BEncode::String(out_str.to_string()),
+                Err(_err) => BEncode::BinaryStr(bytes.to_vec()),
+            },
+            Self::List(list) => BEncode::List(list.iter().map(Self::to_owned).collect()),
+            Self::Dictionary(dict) => BEncode::Dictionary(
+                dict.iter()
+                    .map(|(key, item)| (key.to_vec(), item.to_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> ParseNode<'a> for BEncodeRef<'a> {
+    fn from_int(num: i64, _span: Range<usize>) -> Self {
+        BEncodeRef::Int(num)
+    }
+
+    fn from_big_int(digits: &'a str, _span: Range<usize>) -> Self {
+        BEncodeRef::BigInt(BigInt::from_valid(digits))
+    }
+
+    fn from_bytes(bytes: &'a [u8], _span: Range<usize>, _policy: StringPolicy) -> Self {
+        BEncodeRef::Bytes(bytes)
+    }
+
+    fn new_list(_start: usize) -> Self {
+        BEncodeRef::List(Vec::new())
+    }
+
+    fn new_dict(_start: usize) -> Self {
+        BEncodeRef::Dictionary(BTreeMap::new())
+    }
+
+    fn push(&mut self, item: Self, key: Option<&'a [u8]>) {
+        match (self, key) {
+            (Self::List(list), _) => list.push(item),
+            (Self::Dictionary(dict), Some(key)) => {
+                dict.insert(key, item);
+            }
+            _ => (),
+        }
+    }
+}
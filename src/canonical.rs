@@ -0,0 +1,157 @@
+//! Canonical encoding as described by BEP 3, so that two equal trees always produce identical bytes
+use crate::parser::{self, Events};
+use crate::{BEncode, BigInt, DecodeError, Options, RawBEncode, Token, TokenKind};
+use std::collections::BTreeSet;
+
+impl BEncode {
+    /// Rewrites this object in place into its canonical form:
+    /// every [`BEncode::Raw`] is decoded, and every [`BEncode::BigInt`] loses its leading zeros and becomes a [`BEncode::Int`] if it fits.
+    /// Returns [`DecodeError::DuplicateKey`] if a raw value holds a dictionary with a repeated key, since no single value can be chosen.
+    /// Dictionary keys need no work, as they are always kept sorted by their raw bytes
+    ///
+    /// ## Example
+    /// ```rust
+    /// use bencode_parser::{BEncode, DecodeError, RawBEncode};
+    ///
+    /// let mut big: BEncode = BEncode::List(vec![
+    ///     BEncode::BigInt("-000123456789012345678901234567890".parse().unwrap()),
+    ///     BEncode::BigInt("00042".parse().unwrap()),
+    ///     BEncode::BigInt("-0".parse().unwrap()),
+    /// ]);
+    /// assert_eq!(big.to_canonical_bytes().unwrap(), b"li-123456789012345678901234567890ei42ei0ee");
+    /// big.canonicalize().unwrap();
+    /// assert_eq!(big[1], BEncode::Int(42));
+    /// assert_eq!(big.to_bytes(), b"li-123456789012345678901234567890ei42ei0ee");
+    ///
+    /// // Raw values are decoded, so their keys are sorted and their integers lose their leading zeros
+    /// let mut raw: BEncode = BEncode::Raw(RawBEncode::new(&b"d1:bi007e1:a2:\xff\xfee"[..]).unwrap());
+    /// assert_eq!(raw.to_canonical_bytes().unwrap(), b"d1:a2:\xff\xfe1:bi7ee");
+    /// raw.canonicalize().unwrap();
+    /// assert!(matches!(raw, BEncode::Dictionary(_)));
+    /// assert_eq!(raw.to_bytes(), b"d1:a2:\xff\xfe1:bi7ee");
+    ///
+    /// let mut repeated: BEncode = BEncode::List(vec![BEncode::Raw(RawBEncode::new(&b"d1:ai1e1:ai2ee"[..]).unwrap())]);
+    /// assert_eq!(repeated.to_canonical_bytes(), Err(DecodeError::DuplicateKey { offset: 7 }));
+    /// assert_eq!(repeated.canonicalize(), Err(DecodeError::DuplicateKey { offset: 7 }));
+    /// ```
+    pub fn canonicalize(&mut self) -> Result<(), DecodeError> {
+        match self {
+            Self::List(list) => list.iter_mut().try_for_each(Self::canonicalize),
+            Self::Dictionary(dict) => dict.values_mut().try_for_each(Self::canonicalize),
+            Self::BigInt(digits) => {
+                *self = Self::normalize_int(digits.as_str());
+                Ok(())
+            }
+            Self::Raw(raw) => {
+                let mut value: BEncode = Self::decode_raw(raw)?;
+                value.canonicalize()?;
+                *self = value;
+                Ok(())
+            }
+            Self::Int(_) | Self::String(_) | Self::BinaryStr(_) => Ok(()),
+        }
+    }
+
+    /// Encodes this object in its canonical form without modifying it, see [`BEncode::canonicalize`].
+    /// Keys are written in raw byte order and integers without leading zeros, so equal trees always give identical bytes
+    pub fn to_canonical_bytes(&self) -> Result<Vec<u8>, DecodeError> {
+        let mut output: Vec<u8> = Vec::new();
+        self.encode_canonical(&mut output)?;
+        Ok(output)
+    }
+
+    /// Internal function that writes the canonical encoding of this object to `output`
+    fn encode_canonical(&self, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        match self {
+            Self::List(list) => {
+                output.push(b'l');
+                for item in list {
+                    item.encode_canonical(output)?;
+                }
+                output.push(b'e');
+            }
+            Self::Dictionary(dict) => {
+                output.push(b'd');
+                for (key, item) in dict {
+                    Self::encode_byte_str(key, output).expect("Writing to a Vec<u8> cannot fail");
+                    item.encode_canonical(output)?;
+                }
+                output.push(b'e');
+            }
+            Self::BigInt(digits) => Self::normalize_int(digits.as_str())
+                .encode_into(output)
+                .expect("Writing to a Vec<u8> cannot fail"),
+            Self::Raw(raw) => Self::decode_raw(raw)?.encode_canonical(output)?,
+            Self::Int(_) | Self::String(_) | Self::BinaryStr(_) => {
+                self.encode_into(output)
+                    .expect("Writing to a Vec<u8> cannot fail");
+            }
+        }
+        Ok(())
+    }
+
+    /// Internal function that turns the digits of an Integer into an [`BEncode::Int`] if they fit, or a [`BEncode::BigInt`] without leading zeros
+    pub(crate) fn normalize_int(digits: &str) -> BEncode {
+        let negative: bool = digits.starts_with('-');
+        let magnitude: &str = digits.trim_start_matches('-').trim_start_matches('0');
+        if magnitude.is_empty() {
+            return BEncode::Int(0);
+        }
+
+        let normalized: String = match negative {
+            true => format!("-{}", magnitude),
+            false => magnitude.to_string(),
+        };
+        match normalized.parse::<i64>() {
+            Ok(num) => BEncode::Int(num),
+            Err(_err) => BEncode::BigInt(BigInt::from_valid(normalized)),
+        }
+    }
+
+    /// Internal function that decodes a raw value exactly, keeping every ByteString as it is and integers of any size
+    fn decode_raw(raw: &RawBEncode) -> Result<BEncode, DecodeError> {
+        let options: Options = Options {
+            big_ints: true,
+            ..Options::default()
+        };
+
+        let mut check: DuplicateCheck = DuplicateCheck {
+            keys: Vec::new(),
+            duplicate: None,
+        };
+        parser::visit_value(raw.as_bytes(), &options, &mut check)?;
+        if let Some(offset) = check.duplicate {
+            return Err(DecodeError::DuplicateKey { offset });
+        }
+
+        Ok(raw.decode_exact().clone())
+    }
+}
+
+/// Internal receiver that finds the first dictionary key that appears twice in the same dictionary
+struct DuplicateCheck<'a> {
+    // =====================STATE VARIABLES==========================
+    // `keys` holds the keys seen so far by every open container, innermost last. Lists hold no keys.
+    // `duplicate` is the offset of the first repeated key, if any
+    keys: Vec<BTreeSet<&'a [u8]>>,
+    duplicate: Option<usize>,
+    // ==============================================================
+}
+
+impl<'a> Events<'a> for DuplicateCheck<'a> {
+    fn token(&mut self, token: &Token<'a>, is_key: bool) {
+        match token.kind {
+            TokenKind::Bytes(key) if is_key => {
+                let seen: &mut BTreeSet<&'a [u8]> = self.keys.last_mut().unwrap();
+                if !seen.insert(key) && self.duplicate.is_none() {
+                    self.duplicate = Some(token.span.start);
+                }
+            }
+            TokenKind::ListStart | TokenKind::DictStart => self.keys.push(BTreeSet::new()),
+            TokenKind::End => {
+                self.keys.pop();
+            }
+            _ => (),
+        }
+    }
+}
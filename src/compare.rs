@@ -0,0 +1,189 @@
+//! Equality, ordering and hashing of [`BEncode`] objects, based on the bencode value they stand for rather than how it is stored
+//!
+//! - A [`BEncode::String`] and a [`BEncode::BinaryStr`] holding the same bytes are equal
+//! - A [`BEncode::BigInt`] is equal to an Integer with the same number, whatever leading zeros it has
+//! - A [`BEncode::Raw`] is equal to the value it encodes
+//!
+//! Values of different types are ordered like the first byte of their encoding: ByteStrings, then Dictionaries, Integers and Lists.
+//! ByteStrings are ordered by their raw bytes like canonical dictionary keys, Integers by their number,
+//! and Lists and Dictionaries item by item, with Dictionaries compared entry by entry in key order.
+//!
+//! ## Example
+//! ```rust
+//! use bencode_parser::{bencode, BEncode, RawBEncode};
+//! use std::collections::hash_map::DefaultHasher;
+//! use std::collections::HashSet;
+//! use std::hash::{Hash, Hasher};
+//!
+//! assert_eq!(BEncode::String("ab".to_string()), BEncode::BinaryStr(b"ab".to_vec()));
+//! assert_eq!(BEncode::BigInt("0042".parse().unwrap()), BEncode::Int(42));
+//! assert_eq!(BEncode::Raw(RawBEncode::new(b"li1ee".to_vec()).unwrap()), bencode!([1]));
+//! assert_ne!(BEncode::Int(5), BEncode::String("5".to_string()));
+//!
+//! assert!(bencode!("b") < bencode!("ba"));
+//! assert!(bencode!(9) < bencode!(10));
+//! assert!(bencode!(-1) < BEncode::BigInt("99999999999999999999".parse().unwrap()));
+//! assert!(bencode!("z") < bencode!({}) && bencode!({}) < bencode!(0) && bencode!(0) < bencode!([]));
+//! assert!(bencode!([1, 2]) < bencode!([1, 3]) && bencode!([1]) < bencode!([1, 0]));
+//!
+//! let torrent: BEncode = bencode! { "info" => { "name" => "spam" } };
+//! let copy: BEncode = torrent.clone();
+//! let set: HashSet<BEncode> = HashSet::from([torrent, copy, bencode!({})]);
+//! assert_eq!(set.len(), 2);
+//! assert!(set.contains(&BEncode::Raw(RawBEncode::new(&b"d4:infod4:name4:spamee"[..]).unwrap().into_owned())));
+//!
+//! let set: HashSet<BEncode> = HashSet::from([BEncode::String("ab".to_string()), BEncode::Int(7)]);
+//! assert!(set.contains(&BEncode::BinaryStr(b"ab".to_vec())));
+//! assert!(set.contains(&BEncode::BigInt("0007".parse().unwrap())));
+//!
+//! fn hash(value: &BEncode) -> u64 {
+//!     let mut hasher: DefaultHasher = DefaultHasher::new();
+//!     value.hash(&mut hasher);
+//!     hasher.finish()
+//! }
+//! let pairs: [(BEncode, BEncode); 4] = [
+//!     (BEncode::Raw(RawBEncode::new(b"d1:ai1e1:bli2eee".to_vec()).unwrap()), bencode! { "a" => 1, "b" => [2] }),
+//!     (BEncode::Raw(RawBEncode::new(b"i0042e".to_vec()).unwrap()), BEncode::Int(42)),
+//!     (BEncode::BigInt("-000099999999999999999999".parse().unwrap()), BEncode::BigInt("-99999999999999999999".parse().unwrap())),
+//!     (BEncode::String("ab".to_string()), BEncode::BinaryStr(b"ab".to_vec())),
+//! ];
+//! for (left, right) in &pairs {
+//!     assert_eq!(left, right);
+//!     assert_eq!(hash(left), hash(right));
+//! }
+//!
+//! // Raw values are decoded to be compared, so one nested too deep is rejected up front instead of overflowing the stack later
+//! let deep: Vec<u8> = [vec![b'l'; 200_000], vec![b'e'; 200_000]].concat();
+//! assert!(RawBEncode::new(deep).is_err());
+//! let nested: Vec<u8> = [vec![b'l'; 512], vec![b'e'; 512]].concat();
+//! let raw: BEncode = BEncode::Raw(RawBEncode::new(nested).unwrap());
+//! assert_ne!(raw, bencode!([]));
+//! assert_eq!(HashSet::from([raw.clone()]).len(), 1);
+//! ```
+use crate::BEncode;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+/// Internal view of a [`BEncode`] object with the storage differences removed
+enum Value<'a> {
+    Bytes(&'a [u8]),
+    Dictionary(&'a BTreeMap<Vec<u8>, BEncode>),
+    Int(i64),
+    /// The digits of an Integer that does not fit in an `i64`, without leading zeros
+    BigInt(Cow<'a, str>),
+    List(&'a [BEncode]),
+}
+
+impl<'a> Value<'a> {
+    /// Position of the type in the ordering, following the first byte of its encoding
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Bytes(_) => 0,
+            Self::Dictionary(_) => 1,
+            Self::Int(_) | Self::BigInt(_) => 2,
+            Self::List(_) => 3,
+        }
+    }
+}
+
+impl BEncode {
+    /// Internal function that looks through raw values and storage differences, calling `f` with the value this object stands for.
+    /// A raw value is only decoded the first time, see [`crate::RawBEncode`]
+    fn with_value<R>(&self, f: impl FnOnce(Value) -> R) -> R {
+        match self {
+            Self::String(string) => f(Value::Bytes(string.as_bytes())),
+            Self::BinaryStr(bin) => f(Value::Bytes(bin)),
+            Self::Int(num) => f(Value::Int(*num)),
+            Self::BigInt(digits) => match Self::normalize_int(digits.as_str()) {
+                Self::Int(num) => f(Value::Int(num)),
+                Self::BigInt(digits) => f(Value::BigInt(Cow::Owned(digits.as_str().to_string()))),
+                _ => unreachable!("Integers always normalize to an Integer"),
+            },
+            Self::List(list) => f(Value::List(list)),
+            Self::Dictionary(dict) => f(Value::Dictionary(dict)),
+            Self::Raw(raw) => raw.decode_exact().with_value(f),
+        }
+    }
+
+    /// Internal function that compares two values, see the module documentation
+    fn compare(&self, other: &Self) -> Ordering {
+        if let (Self::Raw(left), Self::Raw(right)) = (self, other) {
+            if left.as_bytes() == right.as_bytes() {
+                return Ordering::Equal;
+            }
+        }
+        self.with_value(|left| {
+            other.with_value(|right| match (&left, &right) {
+                (Value::Bytes(a), Value::Bytes(b)) => a.cmp(b),
+                (Value::Int(a), Value::Int(b)) => a.cmp(b),
+                (Value::Int(_) | Value::BigInt(_), Value::Int(_) | Value::BigInt(_)) => {
+                    compare_digits(&int_digits(&left), &int_digits(&right))
+                }
+                (Value::List(a), Value::List(b)) => a.iter().cmp(b.iter()),
+                (Value::Dictionary(a), Value::Dictionary(b)) => a.iter().cmp(b.iter()),
+                _ => left.rank().cmp(&right.rank()),
+            })
+        })
+    }
+}
+
+/// Internal function that returns the decimal digits of an Integer value
+fn int_digits<'a>(value: &'a Value) -> Cow<'a, str> {
+    match value {
+        Value::Int(num) => Cow::Owned(num.to_string()),
+        Value::BigInt(digits) => Cow::Borrowed(digits),
+        _ => unreachable!("Only called for Integers"),
+    }
+}
+
+/// Internal function that compares two Integers written in decimal without leading zeros
+fn compare_digits(a: &str, b: &str) -> Ordering {
+    match (a.strip_prefix('-'), b.strip_prefix('-')) {
+        (Some(a), Some(b)) => compare_magnitudes(b, a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => compare_magnitudes(a, b),
+    }
+}
+
+/// Internal function that compares two positive numbers written in decimal without leading zeros
+fn compare_magnitudes(a: &str, b: &str) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+impl PartialEq for BEncode {
+    fn eq(&self, other: &Self) -> bool {
+        self.compare(other) == Ordering::Equal
+    }
+}
+
+impl Eq for BEncode {}
+
+impl PartialOrd for BEncode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BEncode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.compare(other)
+    }
+}
+
+impl Hash for BEncode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.with_value(|value| {
+            state.write_u8(value.rank());
+            match value {
+                Value::Bytes(bytes) => bytes.hash(state),
+                Value::Int(num) => num.hash(state),
+                Value::BigInt(digits) => digits.hash(state),
+                Value::List(list) => list.hash(state),
+                Value::Dictionary(dict) => dict.hash(state),
+            }
+        })
+    }
+}
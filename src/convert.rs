@@ -0,0 +1,180 @@
+//! Conversions between [`BEncode`] objects and plain Rust values
+//!
+//! ## Example
+//! ```rust
+//! use bencode_parser::BEncode;
+//!
+//! let big: BEncode = BEncode::from(u64::MAX);
+//! assert_eq!(u64::try_from(&big), Ok(u64::MAX));
+//! assert_eq!(u128::try_from(&BEncode::from(u128::MAX)), Ok(u128::MAX));
+//! assert_eq!(i8::try_from(&BEncode::BigInt("-0128".parse().unwrap())), Ok(-128));
+//! assert_eq!(i64::try_from(&big).unwrap_err().found, "out of range integer");
+//! assert_eq!(u32::try_from(&BEncode::from("5")).unwrap_err().found, "string");
+//! ```
+use crate::{BEncode, BigInt, TypeError};
+use std::collections::BTreeMap;
+
+/// Implements `From` for integer types that always fit in an `i64`
+macro_rules! from_small_int {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for BEncode {
+                fn from(num: $int) -> Self {
+                    BEncode::Int(i64::from(num))
+                }
+            }
+        )*
+    };
+}
+
+/// Implements `From` for integer types that may not fit in an `i64`, which become a [`BEncode::BigInt`] if they do not
+macro_rules! from_large_int {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for BEncode {
+                fn from(num: $int) -> Self {
+                    match i64::try_from(num) {
+                        Ok(num) => BEncode::Int(num),
+                        Err(_err) => BEncode::BigInt(BigInt::from_valid(num.to_string())),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+// `u8` is left out so that `Vec<u8>` is always a ByteString and never a List of Integers
+from_small_int!(i8, i16, i32, i64, u16, u32);
+from_large_int!(isize, usize, u64, i128, u128);
+
+impl From<&str> for BEncode {
+    fn from(string: &str) -> Self {
+        BEncode::String(string.to_string())
+    }
+}
+
+impl From<String> for BEncode {
+    fn from(string: String) -> Self {
+        BEncode::String(string)
+    }
+}
+
+/// Raw bytes always become a [`BEncode::BinaryStr`], even if they are valid UTF-8
+impl From<Vec<u8>> for BEncode {
+    fn from(bytes: Vec<u8>) -> Self {
+        BEncode::BinaryStr(bytes)
+    }
+}
+
+impl<T: Into<BEncode>> From<Vec<T>> for BEncode {
+    fn from(list: Vec<T>) -> Self {
+        BEncode::List(list.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<K: Into<Vec<u8>>, V: Into<BEncode>> From<BTreeMap<K, V>> for BEncode {
+    fn from(dict: BTreeMap<K, V>) -> Self {
+        BEncode::Dictionary(
+            dict.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+/// Implements `TryFrom<&BEncode>` for integer types, failing if the value is not an Integer or does not fit.
+/// A [`BEncode::BigInt`] converts as long as its number fits
+macro_rules! try_into_int {
+    ($($int:ty),*) => {
+        $(
+            impl TryFrom<&BEncode> for $int {
+                type Error = TypeError;
+
+                fn try_from(value: &BEncode) -> Result<Self, TypeError> {
+                    let out_of_range: TypeError = TypeError {
+                        expected: stringify!($int),
+                        found: "out of range integer",
+                    };
+                    let num: BEncode = match value {
+                        BEncode::Int(num) => BEncode::Int(*num),
+                        BEncode::BigInt(digits) => BEncode::normalize_int(digits.as_str()),
+                        _ => {
+                            return Err(TypeError {
+                                expected: stringify!($int),
+                                found: value.type_name(),
+                            })
+                        }
+                    };
+                    match num {
+                        BEncode::Int(num) => <$int>::try_from(num).map_err(|_err| out_of_range),
+                        BEncode::BigInt(digits) => digits.as_str().parse::<$int>().map_err(|_err| out_of_range),
+                        _ => unreachable!("Integers always normalize to an Integer"),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+try_into_int!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl<'a> TryFrom<&'a BEncode> for &'a str {
+    type Error = TypeError;
+
+    fn try_from(value: &'a BEncode) -> Result<Self, TypeError> {
+        value.as_str().ok_or(TypeError {
+            expected: "string",
+            found: value.type_name(),
+        })
+    }
+}
+
+impl TryFrom<&BEncode> for String {
+    type Error = TypeError;
+
+    fn try_from(value: &BEncode) -> Result<Self, TypeError> {
+        <&str>::try_from(value).map(str::to_string)
+    }
+}
+
+impl<'a> TryFrom<&'a BEncode> for &'a [u8] {
+    type Error = TypeError;
+
+    fn try_from(value: &'a BEncode) -> Result<Self, TypeError> {
+        value.as_bytes().ok_or(TypeError {
+            expected: "byte string",
+            found: value.type_name(),
+        })
+    }
+}
+
+/// ByteStrings are copied whether or not they are valid UTF-8
+impl TryFrom<&BEncode> for Vec<u8> {
+    type Error = TypeError;
+
+    fn try_from(value: &BEncode) -> Result<Self, TypeError> {
+        <&[u8]>::try_from(value).map(<[u8]>::to_vec)
+    }
+}
+
+impl<'a> TryFrom<&'a BEncode> for &'a [BEncode] {
+    type Error = TypeError;
+
+    fn try_from(value: &'a BEncode) -> Result<Self, TypeError> {
+        value.as_list().ok_or(TypeError {
+            expected: "list",
+            found: value.type_name(),
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a BEncode> for &'a BTreeMap<Vec<u8>, BEncode> {
+    type Error = TypeError;
+
+    fn try_from(value: &'a BEncode) -> Result<Self, TypeError> {
+        value.as_dict().ok_or(TypeError {
+            expected: "dictionary",
+            found: value.type_name(),
+        })
+    }
+}
@@ -0,0 +1,345 @@
+//! The `Decoder` struct is a resumable push-parser that is fed bencode in chunks, as it arrives from the network
+use crate::parser::{self, ParseNode};
+use crate::{BEncode, DecodeError, Options, TokenKind};
+use std::collections::BTreeMap;
+
+/// The result of feeding a chunk to a [`Decoder`]
+#[derive(Debug)]
+pub enum DecodeStatus {
+    /// The whole chunk was consumed and the value is not complete yet
+    NeedMore,
+    /// A complete value was decoded using the first `usize` bytes of the chunk. The rest of the chunk was not consumed
+    Value(BEncode, usize),
+    /// The input is not valid bencode. The offset is counted from the first byte of the value
+    Error(DecodeError),
+}
+
+/// A resumable bencode parser.
+/// Chunks are passed to [`Decoder::feed`] as they arrive and the decoder keeps the partially built value between calls,
+/// so nothing is parsed twice. Only a scalar that is split across chunks is buffered until it is complete.
+/// After a [`DecodeStatus::Value`] or [`DecodeStatus::Error`] the decoder starts over with the next value
+///
+/// ## Example
+/// ```rust
+/// use bencode_parser::{BEncode, DecodeError, DecodeStatus, Decoder, Options};
+///
+/// // Wherever the input is split, the decoder agrees with `BEncode::try_parse`, errors included
+/// fn decode_in_two(bytes: &[u8], split: usize, strict: bool) -> Result<BEncode, DecodeError> {
+///     let mut decoder: Decoder = Decoder::new(Options { strict, ..Options::default() });
+///     let mut status: DecodeStatus = decoder.feed(&bytes[..split]);
+///     if let DecodeStatus::NeedMore = status {
+///         status = decoder.feed(&bytes[split..]);
+///     }
+///     match status {
+///         DecodeStatus::Value(value, _) => Ok(value),
+///         DecodeStatus::Error(err) => Err(err),
+///         DecodeStatus::NeedMore => panic!("{:?} is complete", bytes),
+///     }
+/// }
+///
+/// let inputs: [&[u8]; 14] = [
+///     b"d8:announce3:url4:infod6:lengthi1024e4:name4:spamee",
+///     b"l4:spami-42eli7eed0:0:ee",
+///     b"i123456789012345e",
+///     b"10:0123456789",
+///     b"de",
+///     b"d:",
+///     b"di1ei2ee",
+///     b"dli1ee1:ae",
+///     b"d1:ai1x2ee",
+///     b"li-ee",
+///     b"l2:abxe",
+///     b"d1:ai1ee",
+///     b"d1:bi1e1:ai2ee",
+///     b"li03ee",
+/// ];
+/// for bytes in inputs {
+///     for strict in [false, true] {
+///         let expected: Result<BEncode, DecodeError> = BEncode::try_parse(bytes, Options { strict, ..Options::default() });
+///         for split in 0..=bytes.len() {
+///             assert_eq!(decode_in_two(bytes, split, strict), expected, "{:?} split at {}", bytes, split);
+///         }
+///     }
+/// }
+/// ```
+pub struct Decoder {
+    options: Options,
+    // =====================STATE VARIABLES==========================
+    // `parents` holds the containers that are still open, innermost last.
+    // `dict_keys` holds one entry per parent: the key waiting for its value, if any.
+    // `partial` holds the beginning of an Integer or ByteString that was cut off at the end of a chunk.
+    // `offset` is the number of bytes of the current value consumed by earlier chunks.
+    // `items` is the number of values started so far, for `Options::max_items`
+    parents: Vec<BEncode>,
+    dict_keys: Vec<Option<Vec<u8>>>,
+    partial: Vec<u8>,
+    offset: usize,
+    items: usize,
+    // ==============================================================
+}
+
+impl Decoder {
+    /// Creates a decoder that parses values using the given [`Options`]
+    pub fn new(options: Options) -> Self {
+        Self {
+            options,
+            parents: Vec::new(),
+            dict_keys: Vec::new(),
+            partial: Vec::new(),
+            offset: 0,
+            items: 0,
+        }
+    }
+
+    /// Feeds the next chunk of input to the decoder
+    pub fn feed(&mut self, chunk: &[u8]) -> DecodeStatus {
+        let res = self.feed_chunk(chunk).and_then(|status| {
+            // Everything consumed so far belongs to the current value
+            let consumed: usize = status
+                .as_ref()
+                .map_or(chunk.len(), |(_, consumed)| *consumed);
+            match self.options.max_total_bytes {
+                Some(max) if self.offset + consumed > max => {
+                    Err(DecodeError::InputTooLarge { offset: max })
+                }
+                _ => Ok(status),
+            }
+        });
+
+        match res {
+            Ok(Some((value, consumed))) => {
+                self.reset();
+                DecodeStatus::Value(value, consumed)
+            }
+            Ok(None) => {
+                self.offset += chunk.len();
+                DecodeStatus::NeedMore
+            }
+            Err(err) => {
+                self.reset();
+                DecodeStatus::Error(err)
+            }
+        }
+    }
+
+    /// Returns true if the decoder is in the middle of a value
+    pub fn is_partial(&self) -> bool {
+        self.offset > 0
+    }
+
+    /// Discards the partially decoded value, if any
+    pub fn reset(&mut self) {
+        self.parents.clear();
+        self.dict_keys.clear();
+        self.partial.clear();
+        self.offset = 0;
+        self.items = 0;
+    }
+
+    /// Internal function that advances the state machine over `chunk`.
+    /// Returns the value and the number of bytes used once it is complete
+    fn feed_chunk(&mut self, chunk: &[u8]) -> Result<Option<(BEncode, usize)>, DecodeError> {
+        let mut idx: usize = 0;
+        loop {
+            // Offset of the current token from the start of the value
+            let token_offset: usize = self.offset + idx - self.partial.len();
+            let byte: u8 = match self.partial.first().or_else(|| chunk.get(idx)) {
+                Some(&byte) => byte,
+                None => return Ok(None),
+            };
+
+            let awaiting_key: bool = matches!(self.parents.last(), Some(BEncode::Dictionary(_)))
+                && matches!(self.dict_keys.last(), Some(None));
+            // Like the parser, an Integer key is only reported once it has been read, and bytes that cannot start
+            // any value are reported as unexpected by the match below
+            if awaiting_key && matches!(byte, b'l' | b'd') {
+                return Err(DecodeError::NonStringKey {
+                    offset: token_offset,
+                });
+            }
+            if self.partial.is_empty() && !awaiting_key && byte != b'e' {
+                // A new value starts here
+                self.items += 1;
+                let depth: usize = self.parents.len() + matches!(byte, b'l' | b'd') as usize;
+                parser::check_limits(depth, self.items, token_offset, &self.options)?;
+            }
+
+            let value: BEncode = match byte {
+                // Integer or String
+                b'i' | b'0'..=b'9' => {
+                    let token_start: usize = idx;
+                    idx = match self.complete_scalar(chunk, idx) {
+                        Some(new_idx) => new_idx,
+                        None => {
+                            // Report errors in the buffered part early, so an oversized or malformed token is not buffered any further
+                            self.check_partial()
+                                .map_err(|err| err.shifted(token_offset))?;
+                            return Ok(None);
+                        }
+                    };
+                    let partial: Vec<u8> = std::mem::take(&mut self.partial);
+                    let token: &[u8] = if partial.is_empty() {
+                        &chunk[token_start..idx]
+                    } else {
+                        &partial
+                    };
+
+                    let value: Option<BEncode> = self
+                        .parse_scalar(token, awaiting_key)
+                        .map_err(|err| err.shifted(token_offset))?;
+                    match value {
+                        Some(value) => value,
+                        None => continue,
+                    }
+                }
+                // List
+                b'l' => {
+                    self.parents.push(BEncode::List(Vec::new()));
+                    self.dict_keys.push(None);
+                    idx += 1;
+                    continue;
+                }
+                // Dictionary
+                b'd' => {
+                    self.parents.push(BEncode::Dictionary(BTreeMap::new()));
+                    self.dict_keys.push(None);
+                    idx += 1;
+                    continue;
+                }
+                b'e' if !self.parents.is_empty() => {
+                    if self.dict_keys.pop().flatten().is_some() {
+                        // The dictionary was closed while a key was waiting for its value
+                        return Err(DecodeError::UnexpectedByte {
+                            offset: token_offset,
+                            byte,
+                        });
+                    }
+                    idx += 1;
+                    self.parents.pop().unwrap()
+                }
+                _ => {
+                    return Err(DecodeError::UnexpectedByte {
+                        offset: token_offset,
+                        byte,
+                    })
+                }
+            };
+
+            match self.parents.last_mut() {
+                Some(parent) => parent.add_child(value, self.dict_keys.last_mut().unwrap().take()),
+                None => return Ok(Some((value, idx))),
+            }
+        }
+    }
+
+    /// Internal function that parses a complete Integer or ByteString token.
+    /// If a dictionary is waiting for a key, the ByteString is stored as the key and `None` is returned
+    fn parse_scalar(
+        &mut self,
+        token: &[u8],
+        awaiting_key: bool,
+    ) -> Result<Option<BEncode>, DecodeError> {
+        if token[0] == b'i' {
+            let value: BEncode = match parser::parse_int(token, 0, &self.options)? {
+                _ if awaiting_key => return Err(DecodeError::NonStringKey { offset: 0 }),
+                (_, TokenKind::BigInt(digits)) => BEncode::from_big_int(digits, 0..token.len()),
+                (_, TokenKind::Int(num)) => BEncode::from_int(num, 0..token.len()),
+                _ => unreachable!("parse_int only returns Integers"),
+            };
+            return Ok(Some(value));
+        }
+
+        let (_, bytes) = parser::parse_bytes(token, 0, &self.options)?;
+        if awaiting_key {
+            let last_key: Option<&[u8]> = match self.parents.last() {
+                Some(BEncode::Dictionary(dict)) => {
+                    dict.keys().next_back().map(|key| key.as_slice())
+                }
+                _ => None,
+            };
+            parser::check_key_order(last_key, bytes, 0, &self.options)?;
+            *self.dict_keys.last_mut().unwrap() = Some(bytes.to_vec());
+            return Ok(None);
+        }
+
+        let key: Option<&[u8]> = self.dict_keys.last().and_then(|key| key.as_deref());
+        Ok(Some(BEncode::from_bytes(
+            bytes,
+            0..token.len(),
+            self.options.policy_for(key),
+        )))
+    }
+
+    /// Internal function that checks the beginning of an incomplete Integer or ByteString held in `self.partial`
+    fn check_partial(&self) -> Result<(), DecodeError> {
+        let res: Result<usize, DecodeError> = if self.partial[0] == b'i' {
+            parser::parse_int(&self.partial, 0, &self.options).map(|(end, _)| end)
+        } else {
+            parser::parse_bytes(&self.partial, 0, &self.options).map(|(end, _)| end)
+        };
+
+        match res {
+            Err(DecodeError::UnexpectedEof { .. }) | Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Internal function that finds the end of the Integer or ByteString starting at `chunk[idx]`, or continuing `self.partial`.
+    /// Returns the index in `chunk` after the token once it is complete. The token is then either `chunk[idx..end]`, or
+    /// `self.partial` if it spans several chunks. Returns `None` after buffering the rest of the chunk if it is incomplete
+    fn complete_scalar(&mut self, chunk: &[u8], mut idx: usize) -> Option<usize> {
+        if self.partial.is_empty() {
+            match Self::token_len(&chunk[idx..]) {
+                Some(len) if len <= chunk.len() - idx => return Some(idx + len),
+                _ => {
+                    self.partial.extend_from_slice(&chunk[idx..]);
+                    return None;
+                }
+            }
+        }
+
+        loop {
+            match Self::token_len(&self.partial) {
+                Some(len) if len <= self.partial.len() => return Some(idx),
+                Some(len) => {
+                    let take: usize = (len - self.partial.len()).min(chunk.len() - idx);
+                    self.partial.extend_from_slice(&chunk[idx..idx + take]);
+                    idx += take;
+                    if self.partial.len() < len {
+                        return None;
+                    }
+                }
+                None => {
+                    // The header is not complete yet, so the token length is not known
+                    let byte: u8 = *chunk.get(idx)?;
+                    self.partial.push(byte);
+                    idx += 1;
+                }
+            }
+        }
+    }
+
+    /// Internal function that returns the length of the token at the start of `bytes`, or `None` if that cannot be known yet.
+    /// When the token is malformed, the length covers the offending byte so the parser can report it
+    fn token_len(bytes: &[u8]) -> Option<usize> {
+        if bytes[0] == b'i' {
+            // An Integer ends at the first byte that cannot be part of it, normally `e`
+            return bytes[1..]
+                .iter()
+                .position(|&b| !matches!(b, b'-' | b'0'..=b'9'))
+                .map(|pos| pos + 2);
+        }
+
+        let colon: usize = bytes.iter().position(|b| !b.is_ascii_digit())?;
+        if bytes[colon] != b':' {
+            return Some(colon + 1);
+        }
+        // Only ASCII digits were accepted above
+        let len: Option<usize> = std::str::from_utf8(&bytes[..colon])
+            .unwrap()
+            .parse::<usize>()
+            .ok()
+            .and_then(|len| len.checked_add(colon + 1));
+        Some(len.unwrap_or(colon + 1))
+    }
+}
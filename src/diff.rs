@@ -0,0 +1,211 @@
+//! Structural differences between two [`BEncode`] objects, and applying them as a patch
+use crate::{BEncode, PatchError, PrettyPrinter};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// One difference between two [`BEncode`] objects, found by [`BEncode::diff`].
+/// `path` addresses the changed value like [`BEncode::get_path`]: each segment is a dictionary key,
+/// or the index of a list item written in decimal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// `value` was added under a dictionary key, or inserted into a list at an index
+    Add { path: Vec<Vec<u8>>, value: BEncode },
+    /// The value was removed from a dictionary or a list
+    Remove { path: Vec<Vec<u8>> },
+    /// The value was replaced by `value`
+    Replace { path: Vec<Vec<u8>>, value: BEncode },
+}
+
+impl Change {
+    /// Returns the path of the changed value
+    pub fn path(&self) -> &[Vec<u8>] {
+        match self {
+            Self::Add { path, .. } | Self::Remove { path } | Self::Replace { path, .. } => path,
+        }
+    }
+
+    /// Returns the path as a JSON Pointer that [`BEncode::pointer`] accepts. Segments that are not valid UTF-8 are converted lossily
+    pub fn pointer(&self) -> String {
+        self.path()
+            .iter()
+            .map(|segment| {
+                let segment = String::from_utf8_lossy(segment);
+                format!("/{}", segment.replace('~', "~0").replace('/', "~1"))
+            })
+            .collect()
+    }
+}
+
+/// Renders the change on a single line, like `replace /info/name: "eggs"`
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let printer: PrettyPrinter = PrettyPrinter {
+            compact: true,
+            ..PrettyPrinter::default()
+        };
+        match self {
+            Self::Add { value, .. } => {
+                write!(f, "add {}: {}", self.pointer(), printer.print(value))
+            }
+            Self::Remove { .. } => write!(f, "remove {}", self.pointer()),
+            Self::Replace { value, .. } => {
+                write!(f, "replace {}: {}", self.pointer(), printer.print(value))
+            }
+        }
+    }
+}
+
+impl BEncode {
+    /// Returns the changes that turn this value into `other` when applied in order.
+    /// Dictionaries are compared key by key. Lists are compared item by item after skipping the items they share at both ends,
+    /// and the remaining items are added or removed one by one. Any other difference replaces the whole value.
+    /// Changes are ordered by path, except that items removed from a list come last index first.
+    /// Values are compared like `==`, so a String and a binary string with the same bytes are not a change
+    ///
+    /// ## Example
+    /// ```rust
+    /// use bencode_parser::{bencode, BEncode, Change};
+    ///
+    /// let old: BEncode = bencode! { "announce" => "udp://a.example", "info" => { "name" => "spam" }, "url-list" => ["a", "b", "c"] };
+    /// let new: BEncode = bencode! { "info" => { "files" => [{ "length" => 1 }], "name" => "eggs" }, "url-list" => ["b", "c"] };
+    /// let changes: Vec<Change> = old.diff(&new);
+    /// let lines: Vec<String> = changes.iter().map(Change::to_string).collect();
+    /// assert_eq!(lines, [
+    ///     "remove /announce",
+    ///     "add /info/files: [{\"length\": 1}]",
+    ///     "replace /info/name: \"eggs\"",
+    ///     "remove /url-list/0",
+    /// ]);
+    ///
+    /// let shifted: BEncode = bencode!(["x", "a", "b", "c"]);
+    /// let lines: Vec<String> = bencode!(["a", "b", "c"]).diff(&shifted).iter().map(Change::to_string).collect();
+    /// assert_eq!(lines, ["add /0: \"x\""]);
+    ///
+    /// let mut patched: BEncode = old.clone();
+    /// patched.apply(&changes).unwrap();
+    /// assert_eq!(patched, new);
+    /// ```
+    pub fn diff(&self, other: &BEncode) -> Vec<Change> {
+        let mut changes: Vec<Change> = Vec::new();
+        self.diff_into(other, &mut Vec::new(), &mut changes);
+        changes
+    }
+
+    /// Internal function that adds the changes from `self` to `other`, both found at `path`, to `changes`
+    fn diff_into(&self, other: &BEncode, path: &mut Vec<Vec<u8>>, changes: &mut Vec<Change>) {
+        match (self, other) {
+            (Self::Dictionary(old), Self::Dictionary(new)) => {
+                let keys: BTreeSet<&Vec<u8>> = old.keys().chain(new.keys()).collect();
+                for key in keys {
+                    path.push(key.clone());
+                    match (old.get(key), new.get(key)) {
+                        (Some(old), Some(new)) => old.diff_into(new, path, changes),
+                        (Some(_), None) => changes.push(Change::Remove { path: path.clone() }),
+                        (None, Some(new)) => changes.push(Change::Add {
+                            path: path.clone(),
+                            value: new.clone(),
+                        }),
+                        (None, None) => unreachable!("The key comes from one of the dictionaries"),
+                    }
+                    path.pop();
+                }
+            }
+            (Self::List(old), Self::List(new)) => {
+                // Items shared at both ends are unchanged, only the items between them are compared
+                let prefix: usize = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+                let suffix: usize = old[prefix..]
+                    .iter()
+                    .rev()
+                    .zip(new[prefix..].iter().rev())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                let old_end: usize = old.len() - suffix;
+                let new_end: usize = new.len() - suffix;
+                let paired: usize = prefix + (old_end - prefix).min(new_end - prefix);
+                for idx in prefix..paired {
+                    path.push(idx.to_string().into_bytes());
+                    old[idx].diff_into(&new[idx], path, changes);
+                    path.pop();
+                }
+                for (idx, value) in new.iter().enumerate().take(new_end).skip(paired) {
+                    path.push(idx.to_string().into_bytes());
+                    changes.push(Change::Add {
+                        path: path.clone(),
+                        value: value.clone(),
+                    });
+                    path.pop();
+                }
+                // Removed from the last one, so every index is still valid when the changes are applied in order
+                for idx in (paired..old_end).rev() {
+                    path.push(idx.to_string().into_bytes());
+                    changes.push(Change::Remove { path: path.clone() });
+                    path.pop();
+                }
+            }
+            _ if self != other => changes.push(Change::Replace {
+                path: path.clone(),
+                value: other.clone(),
+            }),
+            _ => {}
+        }
+    }
+
+    /// Applies `changes` in order, like those returned by [`BEncode::diff`].
+    /// Adding to a list inserts at the index, shifting the following items, and adding to a dictionary replaces any existing value.
+    /// The empty path refers to this value itself, which can only be replaced.
+    /// If a change fails, the changes before it stay applied
+    pub fn apply(&mut self, changes: &[Change]) -> Result<(), PatchError> {
+        for (idx, change) in changes.iter().enumerate() {
+            self.apply_change(change, idx)?;
+        }
+        Ok(())
+    }
+
+    /// Internal function that applies one change, `idx` being its position for errors
+    fn apply_change(&mut self, change: &Change, idx: usize) -> Result<(), PatchError> {
+        let not_found: PatchError = PatchError::PathNotFound { change: idx };
+        let Some((last, parent)) = change.path().split_last() else {
+            return match change {
+                Change::Replace { value, .. } => {
+                    *self = value.clone();
+                    Ok(())
+                }
+                _ => Err(not_found),
+            };
+        };
+        let parent: &mut BEncode = self.get_path_mut(parent).ok_or(not_found.clone())?;
+        match (parent, change) {
+            (Self::Dictionary(dict), Change::Add { value, .. }) => {
+                dict.insert(last.clone(), value.clone());
+            }
+            (Self::Dictionary(dict), Change::Remove { .. }) => {
+                dict.remove(last).ok_or(not_found)?;
+            }
+            (Self::Dictionary(dict), Change::Replace { value, .. }) => {
+                *dict.get_mut(last).ok_or(not_found)? = value.clone();
+            }
+            (Self::List(list), change) => {
+                let index: usize = Self::segment_index(last).ok_or(not_found.clone())?;
+                match change {
+                    Change::Add { value, .. } if index <= list.len() => {
+                        list.insert(index, value.clone())
+                    }
+                    Change::Remove { .. } if index < list.len() => {
+                        list.remove(index);
+                    }
+                    Change::Replace { value, .. } if index < list.len() => {
+                        list[index] = value.clone()
+                    }
+                    _ => return Err(not_found),
+                }
+            }
+            (parent, _) => {
+                return Err(PatchError::NotAContainer {
+                    change: idx,
+                    found: parent.type_name(),
+                })
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+//! The `DecodeError` enum describes why a bencode input could not be parsed, `ReadError` extends it with I/O failures,
+//! `TypeError` describes a value that does not have the expected type, `QueryError` a malformed query
+//! and `PatchError` a change that cannot be applied
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// The error returned by [`crate::BEncode::try_parse`].
+/// Every variant carries the byte offset in the input at which the problem was detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended before the current value was complete
+    UnexpectedEof { offset: usize },
+    /// An integer (`i...e`) did not contain a valid number
+    InvalidInteger { offset: usize },
+    /// An integer did not fit in an `i64` and [`crate::Options::big_ints`] was not set
+    IntegerOverflow { offset: usize },
+    /// A ByteString length prefix (`<len>:`) was missing, malformed or too large
+    InvalidLength { offset: usize },
+    /// A dictionary key was something other than a ByteString
+    NonStringKey { offset: usize },
+    /// A byte was found that cannot start or end a value at this position
+    UnexpectedByte { offset: usize, byte: u8 },
+    /// A complete value was parsed but the input continues after it
+    TrailingData { offset: usize },
+    /// Strict mode: an integer had leading zeros or was negative zero
+    NonCanonicalInteger { offset: usize },
+    /// Strict mode: a ByteString length prefix had leading zeros
+    NonCanonicalLength { offset: usize },
+    /// Strict mode: a dictionary key was not greater than the previous key in raw byte order
+    UnsortedKey { offset: usize },
+    /// Strict mode: a dictionary key appeared more than once
+    DuplicateKey { offset: usize },
+    /// A List or Dictionary was nested deeper than [`crate::Options::max_depth`]
+    DepthLimitExceeded { offset: usize },
+    /// A ByteString was longer than [`crate::Options::max_string_len`]
+    StringTooLong { offset: usize },
+    /// The value held more items than [`crate::Options::max_items`]
+    TooManyItems { offset: usize },
+    /// The value spanned more bytes than [`crate::Options::max_total_bytes`]. The offset is the limit itself
+    InputTooLarge { offset: usize },
+}
+
+impl DecodeError {
+    /// Returns the byte offset in the input at which the error was detected
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::UnexpectedEof { offset }
+            | Self::InvalidInteger { offset }
+            | Self::IntegerOverflow { offset }
+            | Self::InvalidLength { offset }
+            | Self::NonStringKey { offset }
+            | Self::UnexpectedByte { offset, .. }
+            | Self::TrailingData { offset }
+            | Self::NonCanonicalInteger { offset }
+            | Self::NonCanonicalLength { offset }
+            | Self::UnsortedKey { offset }
+            | Self::DuplicateKey { offset }
+            | Self::DepthLimitExceeded { offset }
+            | Self::StringTooLong { offset }
+            | Self::TooManyItems { offset }
+            | Self::InputTooLarge { offset } => *offset,
+        }
+    }
+
+    /// Internal function that moves the offset by `by` bytes, for errors found in a slice of the whole input
+    pub(crate) fn shifted(mut self, by: usize) -> Self {
+        match &mut self {
+            Self::UnexpectedEof { offset }
+            | Self::InvalidInteger { offset }
+            | Self::IntegerOverflow { offset }
+            | Self::InvalidLength { offset }
+            | Self::NonStringKey { offset }
+            | Self::UnexpectedByte { offset, .. }
+            | Self::TrailingData { offset }
+            | Self::NonCanonicalInteger { offset }
+            | Self::NonCanonicalLength { offset }
+            | Self::UnsortedKey { offset }
+            | Self::DuplicateKey { offset }
+            | Self::DepthLimitExceeded { offset }
+            | Self::StringTooLong { offset }
+            | Self::TooManyItems { offset }
+            | Self::InputTooLarge { offset } => *offset += by,
+        }
+        self
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof { offset } => {
+                write!(f, "unexpected end of input at byte {}", offset)
+            }
+            Self::InvalidInteger { offset } => write!(f, "invalid integer at byte {}", offset),
+            Self::IntegerOverflow { offset } => {
+                write!(f, "integer out of range at byte {}", offset)
+            }
+            Self::InvalidLength { offset } => {
+                write!(f, "invalid string length prefix at byte {}", offset)
+            }
+            Self::NonStringKey { offset } => {
+                write!(f, "non-string dictionary key at byte {}", offset)
+            }
+            Self::UnexpectedByte { offset, byte } => {
+                write!(f, "unexpected byte 0x{:02x} at byte {}", byte, offset)
+            }
+            Self::TrailingData { offset } => write!(f, "trailing data at byte {}", offset),
+            Self::NonCanonicalInteger { offset } => {
+                write!(f, "non-canonical integer at byte {}", offset)
+            }
+            Self::NonCanonicalLength { offset } => {
+                write!(f, "non-canonical string length prefix at byte {}", offset)
+            }
+            Self::UnsortedKey { offset } => {
+                write!(f, "dictionary key out of order at byte {}", offset)
+            }
+            Self::DuplicateKey { offset } => {
+                write!(f, "duplicate dictionary key at byte {}", offset)
+            }
+            Self::DepthLimitExceeded { offset } => {
+                write!(f, "nesting depth limit exceeded at byte {}", offset)
+            }
+            Self::StringTooLong { offset } => {
+                write!(f, "string length limit exceeded at byte {}", offset)
+            }
+            Self::TooManyItems { offset } => {
+                write!(f, "item count limit exceeded at byte {}", offset)
+            }
+            Self::InputTooLarge { offset } => {
+                write!(f, "input size limit exceeded at byte {}", offset)
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// The error returned by [`crate::BEncode::from_reader`]
+#[derive(Debug)]
+pub enum ReadError {
+    /// The underlying reader failed
+    Io(io::Error),
+    /// The bytes read were not valid bencode. The offset is counted from where the reader was positioned when the call started
+    Decode(DecodeError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "I/O error: {}", err),
+            Self::Decode(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Decode(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<DecodeError> for ReadError {
+    fn from(err: DecodeError) -> Self {
+        Self::Decode(err)
+    }
+}
+
+/// The error returned when a [`crate::BEncode`] object does not have the type an operation needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeError {
+    /// The type that was needed, like `"list"` or `"u32"`
+    pub expected: &'static str,
+    /// The type that was found, like `"dictionary"`, or `"out of range integer"` for a number that does not fit
+    pub found: &'static str,
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {}, found {}", self.expected, self.found)
+    }
+}
+
+impl Error for TypeError {}
+
+/// The error returned when a query passed to [`crate::Query::parse`] is malformed.
+/// Every variant carries the byte offset in the query at which the problem was detected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// A key between two dots, or at the start or end of the query, was empty
+    EmptyKey { offset: usize },
+    /// A `[` was not closed by a `]`
+    UnclosedBracket { offset: usize },
+    /// The text between `[` and `]` was neither `*` nor a list index
+    InvalidIndex { offset: usize },
+    /// A character other than `.` or `[` followed a `]`
+    UnexpectedChar { offset: usize, ch: char },
+}
+
+impl QueryError {
+    /// Returns the byte offset in the query at which the error was detected
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::EmptyKey { offset }
+            | Self::UnclosedBracket { offset }
+            | Self::InvalidIndex { offset }
+            | Self::UnexpectedChar { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyKey { offset } => write!(f, "empty key at byte {}", offset),
+            Self::UnclosedBracket { offset } => write!(f, "unclosed bracket at byte {}", offset),
+            Self::InvalidIndex { offset } => write!(f, "invalid list index at byte {}", offset),
+            Self::UnexpectedChar { offset, ch } => {
+                write!(f, "unexpected character {:?} at byte {}", ch, offset)
+            }
+        }
+    }
+}
+
+impl Error for QueryError {}
+
+/// The error returned by [`crate::BEncode::apply`] when a change cannot be applied.
+/// `change` is the position of the failing change in the slice, the changes before it have already been applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// The path leads nowhere: a parent is missing, a list index is out of range,
+    /// or the value to remove or replace does not exist
+    PathNotFound { change: usize },
+    /// The parent of the changed value is neither a list nor a dictionary
+    NotAContainer { change: usize, found: &'static str },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PathNotFound { change } => write!(f, "change {}: path not found", change),
+            Self::NotAContainer { change, found } => write!(
+                f,
+                "change {}: expected list or dictionary, found {}",
+                change, found
+            ),
+        }
+    }
+}
+
+impl Error for PatchError {}
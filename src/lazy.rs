@@ -0,0 +1,218 @@
+//! The `LazyDocument` struct indexes a bencode buffer once and decodes individual values only when they are asked for
+use crate::parser::{self, Events, ParseNode};
+use crate::{BEncode, BEncodeRef, DecodeError, Options, StringPolicy, Token, TokenKind};
+use std::ops::Range;
+
+/// A bencode buffer along with an index of where every value and dictionary key starts and ends.
+/// Building the index checks the whole input like [`BEncode::try_parse`], but nothing is decoded or copied,
+/// so looking up a few fields of a large torrent is much cheaper than building the full [`BEncode`] tree
+///
+/// ## Example
+/// ```rust
+/// use bencode_parser::{LazyDocument, Options};
+///
+/// let bytes: &[u8] = b"d4:infod6:lengthi42e4:name4:spamee";
+/// let doc: LazyDocument = LazyDocument::new(bytes, Options::default()).unwrap();
+/// let name = doc.get("info").and_then(|info| info.get("name"));
+/// assert_eq!(name.and_then(|name| name.as_bytes()), Some(&b"spam"[..]));
+/// ```
+pub struct LazyDocument<'a> {
+    bytes: &'a [u8],
+    options: Options,
+    // Every value in the order it appears in the input, so the children of a container follow it directly
+    index: Vec<Entry>,
+}
+
+/// Internal struct recording where a value is in the input
+struct Entry {
+    span: Range<usize>,
+    /// The contents of the key this value is stored under, if its parent is a Dictionary
+    key: Option<Range<usize>>,
+    /// The index of the first entry after this value and all of its children
+    next: usize,
+}
+
+impl<'a> LazyDocument<'a> {
+    /// Checks `bytes` and builds the index. The input must contain exactly one bencode value,
+    /// and the same errors as [`BEncode::try_parse`] are returned
+    pub fn new(bytes: &'a [u8], options: Options) -> Result<Self, DecodeError> {
+        let mut builder: IndexBuilder = IndexBuilder {
+            index: Vec::new(),
+            open: Vec::new(),
+            key: None,
+        };
+        let idx: usize = parser::visit_value(bytes, &options, &mut builder)?;
+        if idx < bytes.len() {
+            return Err(DecodeError::TrailingData { offset: idx });
+        }
+
+        Ok(Self {
+            bytes,
+            options,
+            index: builder.index,
+        })
+    }
+
+    /// Returns the top level value
+    pub fn root(&self) -> LazyValue<'_, 'a> {
+        LazyValue { doc: self, idx: 0 }
+    }
+
+    /// Returns the value stored under `key` if the top level value is a Dictionary
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<LazyValue<'_, 'a>> {
+        self.root().get(key)
+    }
+}
+
+/// A single value of a [`LazyDocument`], which is only decoded when asked for
+#[derive(Clone, Copy)]
+pub struct LazyValue<'d, 'a> {
+    doc: &'d LazyDocument<'a>,
+    idx: usize,
+}
+
+impl<'d, 'a> LazyValue<'d, 'a> {
+    /// Returns the value stored under `key` if this is a Dictionary.
+    /// If the key appears more than once the last value is returned, like [`BEncode::try_parse`] keeps
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<LazyValue<'d, 'a>> {
+        if !self.is_dict() {
+            return None;
+        }
+        self.children()
+            .filter(|child| child.key() == Some(key.as_ref()))
+            .last()
+    }
+
+    /// Returns the item at `idx` if this is a List
+    pub fn item(&self, idx: usize) -> Option<LazyValue<'d, 'a>> {
+        if !self.is_list() {
+            return None;
+        }
+        self.children().nth(idx)
+    }
+
+    /// Returns an iterator over the items of a List or the values of a Dictionary, in input order.
+    /// Use [`LazyValue::key`] to find out which key each value of a Dictionary is stored under
+    pub fn children(&self) -> impl Iterator<Item = LazyValue<'d, 'a>> {
+        let doc: &'d LazyDocument<'a> = self.doc;
+        let end: usize = doc.index[self.idx].next;
+        let first: Option<usize> = Some(self.idx + 1).filter(|&idx| idx < end);
+        std::iter::successors(first, move |&idx| {
+            Some(doc.index[idx].next).filter(|&next| next < end)
+        })
+        .map(move |idx| LazyValue { doc, idx })
+    }
+
+    /// Returns the number of items of a List or entries of a Dictionary, and 0 for anything else
+    pub fn len(&self) -> usize {
+        self.children().count()
+    }
+
+    /// Returns true if this is not a List or Dictionary with at least one item
+    pub fn is_empty(&self) -> bool {
+        self.children().next().is_none()
+    }
+
+    /// Returns the key this value is stored under, if its parent is a Dictionary
+    pub fn key(&self) -> Option<&'a [u8]> {
+        let key: &Range<usize> = self.doc.index[self.idx].key.as_ref()?;
+        Some(&self.doc.bytes[key.clone()])
+    }
+
+    /// Returns true if this value is a List
+    pub fn is_list(&self) -> bool {
+        self.raw()[0] == b'l'
+    }
+
+    /// Returns true if this value is a Dictionary
+    pub fn is_dict(&self) -> bool {
+        self.raw()[0] == b'd'
+    }
+
+    /// Returns the number if this value is an Integer that fits in an `i64`
+    pub fn as_int(&self) -> Option<i64> {
+        if self.raw()[0] != b'i' {
+            return None;
+        }
+        match parser::parse_int(self.raw(), 0, &self.doc.options) {
+            Ok((_, TokenKind::Int(num))) => Some(num),
+            _ => None,
+        }
+    }
+
+    /// Returns the raw contents if this value is a ByteString
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        if !self.raw()[0].is_ascii_digit() {
+            return None;
+        }
+        parser::parse_bytes(self.raw(), 0, &self.doc.options)
+            .ok()
+            .map(|(_, bytes)| bytes)
+    }
+
+    /// Returns the range of input bytes this value occupies
+    pub fn span(&self) -> Range<usize> {
+        self.doc.index[self.idx].span.clone()
+    }
+
+    /// Returns the exact input bytes of this value
+    pub fn raw(&self) -> &'a [u8] {
+        &self.doc.bytes[self.span()]
+    }
+
+    /// Decodes this value and everything inside it into a [`BEncode`] object, using the options of the document.
+    /// A ByteString is decoded with the [`crate::StringPolicy`] of the key it is stored under
+    pub fn decode(&self) -> BEncode {
+        if let Some(bytes) = self.as_bytes() {
+            let policy: StringPolicy = self.doc.options.policy_for(self.key());
+            return BEncode::from_bytes(bytes, self.span(), policy);
+        }
+        let (value, _) = parser::parse_value(self.raw(), &self.doc.options)
+            .expect("The value was checked when the index was built");
+        value
+    }
+
+    /// Decodes this value and everything inside it into a [`BEncodeRef`] object, borrowing from the input
+    pub fn decode_ref(&self) -> BEncodeRef<'a> {
+        let (value, _) = parser::parse_value(self.raw(), &self.doc.options)
+            .expect("The value was checked when the index was built");
+        value
+    }
+}
+
+/// Internal receiver that records the position of every value
+struct IndexBuilder {
+    index: Vec<Entry>,
+    // =====================STATE VARIABLES==========================
+    // `open` holds the index entries of the containers that are still open, innermost last.
+    // `key` holds the contents of the key waiting for its value, if any
+    open: Vec<usize>,
+    key: Option<Range<usize>>,
+    // ==============================================================
+}
+
+impl<'a> Events<'a> for IndexBuilder {
+    fn token(&mut self, token: &Token<'a>, is_key: bool) {
+        match token.kind {
+            TokenKind::Bytes(key) if is_key => {
+                self.key = Some(token.span.end - key.len()..token.span.end);
+            }
+            TokenKind::End => {
+                let idx: usize = self.open.pop().unwrap();
+                self.index[idx].span.end = token.span.end;
+                self.index[idx].next = self.index.len();
+            }
+            _ => {
+                let idx: usize = self.index.len();
+                self.index.push(Entry {
+                    span: token.span.clone(),
+                    key: self.key.take(),
+                    next: idx + 1,
+                });
+                if matches!(token.kind, TokenKind::ListStart | TokenKind::DictStart) {
+                    self.open.push(idx);
+                }
+            }
+        }
+    }
+}
@@ -1,318 +1,323 @@
 //! > **A Bencode Parser written in Rust**
 //!
 //! ## Example
-//! ```rust
-//! use bencode::{BEncode, Options};
+//! ```rust,no_run
+//! use bencode_parser::{BEncode, Options};
+//! use std::fs;
+//! use std::path::PathBuf;
 //!
 //! let path: PathBuf = PathBuf::from("./examples/big-buck-bunny.torrent");
 //! let bytes = fs::read(path).expect("Couldn't Read File!");
 //! let options: Options = Options::default();
-//! let res: BEncode = BEncode::parse(bytes, options);
-//! println!("Decoded Object: {:?}", res);
+//! let res: BEncode = BEncode::try_parse(&bytes, options).expect("Invalid bencode!");
+//! println!("Decoded Object: {}", res);
 //! ```
 
+mod access;
+mod big_int;
+mod borrowed;
+mod canonical;
+mod compare;
+mod convert;
+mod decoder;
+mod diff;
+mod error;
+mod lazy;
+mod macros;
+mod many;
+mod mutate;
 mod options;
-
-pub use options::Options;
-use std::cmp::Ordering;
+mod parser;
+mod pretty;
+mod query;
+mod raw;
+mod reader;
+mod recover;
+mod spanned;
+mod tokenizer;
+mod visitor;
+
+pub use big_int::BigInt;
+pub use borrowed::BEncodeRef;
+pub use decoder::{DecodeStatus, Decoder};
+pub use diff::Change;
+pub use error::{DecodeError, PatchError, QueryError, ReadError, TypeError};
+pub use lazy::{LazyDocument, LazyValue};
+pub use many::ParseMany;
+pub use options::{Options, StringPolicy};
+use parser::ParseNode;
+pub use pretty::{BinaryFormat, PrettyPrinter};
+pub use query::{Query, QueryMatches};
+pub use raw::RawBEncode;
+pub use recover::{Diagnostic, Severity};
+pub use spanned::{Spanned, SpannedBEncode};
 use std::collections::BTreeMap;
 use std::fmt;
+use std::io::{self, Write};
+use std::ops::Range;
+pub use tokenizer::{Token, TokenKind, Tokenizer};
+pub use visitor::Visitor;
 
 /// The BEncode Object.
 /// This enum wraps the data types supported by bencode objects, with an addition of `String`.
 /// The `String` variant holds the `BinaryStr` which are valid UTF-8 strings.
+#[derive(Clone)]
 pub enum BEncode {
     /// The `Int` variant holds the integers parsed from bencode
-    Int(isize),
+    Int(i64),
+    /// The `BigInt` variant holds the decimal digits, with an optional leading `-`, of integers that do not fit in an `i64`.
+    /// It is only produced when [`Options::big_ints`] is set, so huge values round-trip instead of failing to parse
+    BigInt(BigInt),
     /// The `String` variant holds parsed bencode ByteStrings that have valid UTF-8 characters
     String(String),
     /// The `List` variant holds parsed bencode Lists. They can hold any of the bencode types as children
     List(Vec<BEncode>),
-    /// The `Dictionary` variant holds parsed bencode Dictionaries. They are similar to `BTreeMap`, but the keys can only be [`BEncode::String`]. Though the value can be of any of the bencode types
-    Dictionary(BTreeMap<String, BEncode>),
+    /// The `Dictionary` variant holds parsed bencode Dictionaries. The keys are the raw bytes of bencode ByteStrings, so binary keys are kept losslessly, and they are sorted bytewise. The value can be of any of the bencode types.
+    /// Use [`BEncode::get`] to look up a value with a `&str` key
+    Dictionary(BTreeMap<Vec<u8>, BEncode>),
     /// The `BinaryStr` variant holds parsed bencode ByteStrings that do not have valid UTF-8 characters. They are useful for dealing with the `pieces` property of a torrent file as they contain binary strings.
     BinaryStr(Vec<u8>),
+    /// The `Raw` variant holds an already encoded value that is written out unchanged by the encoder. The parsers never produce it,
+    /// and it is opaque to lookups like [`BEncode::get`]. Use [`RawBEncode::decode`] to look inside it
+    Raw(RawBEncode<'static>),
 }
 
+/// Strings are quoted so they cannot be mistaken for integers, and binary strings are shown as `[Binary String]`.
+/// Use the `Display` implementation or a [`PrettyPrinter`] to see their bytes
+///
+/// ## Example
+/// ```rust
+/// use bencode_parser::{bencode, BEncode};
+///
+/// let value: BEncode = BEncode::List(vec![bencode!(5), bencode!("5"), BEncode::BinaryStr(vec![0xff])]);
+/// assert_eq!(format!("{:?}", value), "[5, \"5\", [Binary String]]");
+/// ```
 impl fmt::Debug for BEncode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let output = match self {
             Self::Int(value) => value.to_string(),
-            Self::String(value) => value.clone(),
+            Self::BigInt(value) => value.to_string(),
+            Self::String(value) => format!("{:?}", value),
             Self::List(value) => format!("{:?}", value),
-            Self::Dictionary(value) => format!("{:?}", value),
+            Self::Dictionary(value) => {
+                let keys = value.keys().map(|key| DebugKey(key));
+                format!("{:?}", BTreeMap::from_iter(keys.zip(value.values())))
+            }
             Self::BinaryStr(_) => "[Binary String]".to_string(),
+            Self::Raw(_) => "[Raw Value]".to_string(),
         };
 
         write!(f, "{}", output)
     }
 }
 
-impl BEncode {
-    /// This function returns the parsed [`BEncode`] object, given a valid path to a file containing bencode.
-    /// returns a `Bencode::Int(-1)` if the bencode cannot be parsed
-    pub fn parse(bytes: Vec<u8>, options: Options) -> Self {
-        // =====================STATE VARIABLES==========================
-        let mut parents: Vec<BEncode> = Vec::new();
-        let mut dict_keys: Vec<String> = Vec::new();
-        // ==============================================================
+/// Internal wrapper that formats a raw dictionary key as a quoted string, or `[Binary String]` if it is not valid UTF-8
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct DebugKey<'a>(&'a [u8]);
 
-        let mut idx: usize = 0;
-        let len: usize = bytes.len();
-        while idx < len {
-            let ch: String = String::from_utf8(vec![bytes[idx]]).unwrap();
-            idx += 1;
+impl fmt::Debug for DebugKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match std::str::from_utf8(self.0) {
+            Ok(key) => write!(f, "{:?}", key),
+            Err(_err) => write!(f, "[Binary String]"),
+        }
+    }
+}
 
-            match ch.as_str() {
-                // Integer
-                "i" => {
-                    let (new_idx, num) = Self::parse_int(&bytes, idx - 1);
-                    idx = new_idx;
-                    if !parents.is_empty() {
-                        let mut parent: BEncode = parents.pop().unwrap();
+impl BEncode {
+    /// This function returns the parsed [`BEncode`] object, given the bytes of a bencode file.
+    /// returns a `Bencode::Int(-1)` if the bencode cannot be parsed. Any bytes after the first complete value are ignored.
+    /// Use [`BEncode::try_parse`] to find out why parsing failed, or [`BEncode::parse_prefix`] to find out where the value ended
+    pub fn parse(bytes: Vec<u8>, options: Options) -> Self {
+        match parser::parse_value(&bytes, &options) {
+            Ok((root, _)) => root,
+            Err(_err) => BEncode::Int(-1),
+        }
+    }
 
-                        match parent {
-                            BEncode::List(_) => {
-                                parent.push(num, None);
-                                parents.push(parent);
-                            }
-                            BEncode::Dictionary(_) => {
-                                if dict_keys.is_empty() {
-                                    println!(
-                                        "[BEncode Error] Cannot use Int as key for Dictionary!"
-                                    );
-                                } else {
-                                    parent.push(num, Some(dict_keys.pop().unwrap()));
-                                }
-                                parents.push(parent);
-                            }
-                            _ => (),
-                        }
-                    }
-                }
-                // String
-                c if c.chars().next().unwrap().is_numeric() => {
-                    let (new_idx, out_str) = Self::parse_str(&bytes, idx - 1, options.parse_hex);
-                    idx = new_idx;
-                    if !parents.is_empty() {
-                        let mut parent: BEncode = parents.pop().unwrap();
+    /// This function returns the parsed [`BEncode`] object, or a [`DecodeError`] describing the first problem found in the input.
+    /// The input must contain exactly one bencode value, otherwise [`DecodeError::TrailingData`] is returned
+    pub fn try_parse(bytes: &[u8], options: Options) -> Result<Self, DecodeError> {
+        let (root, idx) = parser::parse_value(bytes, &options)?;
+        if idx < bytes.len() {
+            return Err(DecodeError::TrailingData { offset: idx });
+        }
+        Ok(root)
+    }
 
-                        match parent {
-                            BEncode::List(_) => {
-                                parent.push(out_str, None);
-                                parents.push(parent);
-                            }
-                            BEncode::Dictionary(_) => {
-                                if dict_keys.len() <= parents.len() {
-                                    dict_keys.push(format!("{:?}", out_str));
-                                } else {
-                                    parent.push(out_str, Some(dict_keys.pop().unwrap()));
-                                }
-                                parents.push(parent);
-                            }
-                            _ => (),
-                        }
-                    }
-                }
-                // List
-                "l" => {
-                    parents.push(BEncode::List(Vec::new()));
-                }
-                // Dictionary
-                "d" => {
-                    parents.push(BEncode::Dictionary(BTreeMap::new()));
-                }
-                "e" => match parents.len().cmp(&1) {
-                    Ordering::Greater => {
-                        let parent: BEncode = parents.pop().unwrap();
+    /// This function parses the bencode value at the start of `bytes` and returns it along with the number of bytes it used.
+    /// Anything after the value is left alone, like the raw payload that follows the dictionary in a `ut_metadata` message
+    ///
+    /// ## Example
+    /// ```rust
+    /// use bencode_parser::{BEncode, DecodeError, Options};
+    ///
+    /// let message: &[u8] = b"d8:msg_typei1e5:piecei0ee<piece data>";
+    /// let (header, used) = BEncode::parse_prefix(message, Options::default()).unwrap();
+    /// assert_eq!(header.get("piece").and_then(BEncode::as_int), Some(0));
+    /// assert_eq!(&message[used..], b"<piece data>");
+    /// assert_eq!(BEncode::parse_prefix(b"li1e", Options::default()), Err(DecodeError::UnexpectedEof { offset: 4 }));
+    /// ```
+    pub fn parse_prefix(bytes: &[u8], options: Options) -> Result<(Self, usize), DecodeError> {
+        parser::parse_value(bytes, &options)
+    }
 
-                        let mut root: BEncode = parents.pop().unwrap();
+    /// Returns an iterator over the values in `bytes`, which holds several bencode values one after another.
+    /// The iterator stops after the last value, or after yielding the first error, whose offset is counted from the start of `bytes`
+    ///
+    /// ## Example
+    /// ```rust
+    /// use bencode_parser::{BEncode, DecodeError, Options};
+    ///
+    /// let values: Vec<Result<BEncode, DecodeError>> = BEncode::parse_many(b"i1e4:spamle", Options::default()).collect();
+    /// assert_eq!(values, [Ok(BEncode::Int(1)), Ok(BEncode::String("spam".to_string())), Ok(BEncode::List(Vec::new()))]);
+    ///
+    /// let mut many = BEncode::parse_many(b"i1eli2ei-0xe", Options::default());
+    /// assert_eq!(many.next(), Some(Ok(BEncode::Int(1))));
+    /// assert_eq!(many.position(), 3);
+    /// assert_eq!(many.next(), Some(Err(DecodeError::InvalidInteger { offset: 7 })));
+    /// assert_eq!(many.next(), None);
+    /// ```
+    pub fn parse_many(bytes: &[u8], options: Options) -> ParseMany<'_> {
+        ParseMany::new(bytes, options)
+    }
 
-                        match root {
-                            BEncode::List(_) => {
-                                root.push(parent, None);
-                                parents.push(root);
-                            }
-                            BEncode::Dictionary(_) => {
-                                if dict_keys.is_empty() {
-                                    println!("[BEncode Error] Cannot use Non-String BEncode Object as Dictionary Key!");
-                                } else {
-                                    root.push(parent, Some(dict_keys.pop().unwrap()));
-                                }
-                                parents.push(root);
-                            }
-                            _ => (),
-                        }
-                    }
-                    Ordering::Equal => {
-                        let root: BEncode = parents.pop().unwrap();
-                        return root;
-                    }
-                    _ => (),
-                },
-                _ => println!("Nothing"),
-            }
+    /// Returns the value stored under `key` if this is a [`BEncode::Dictionary`].
+    /// `key` can be anything that can be viewed as bytes, like `&str`, `String` or `&[u8]`
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<&BEncode> {
+        match self {
+            Self::Dictionary(dict) => dict.get(key.as_ref()),
+            _ => None,
         }
+    }
 
-        BEncode::Int(-1)
+    /// Returns a mutable reference to the value stored under `key` if this is a [`BEncode::Dictionary`]
+    pub fn get_mut<K: AsRef<[u8]>>(&mut self, key: K) -> Option<&mut BEncode> {
+        match self {
+            Self::Dictionary(dict) => dict.get_mut(key.as_ref()),
+            _ => None,
+        }
     }
 
-    /// Encodes the given [`BEncode`] object recursively to bencode and returns the encoded [`String`]
+    /// Encodes the given [`BEncode`] object recursively to bencode and returns the encoded [`String`].
+    /// ByteStrings that are not valid UTF-8 cannot be represented in a [`String`] and are replaced with `U+FFFD`, so the output is not byte-exact for binary data
+    #[deprecated(
+        note = "use `BEncode::to_bytes` or `BEncode::encode_into`, which emit the exact bencode bytes"
+    )]
     pub fn encode(object: &Self) -> String {
-        let mut output: String = String::new();
+        String::from_utf8_lossy(&object.to_bytes()).into_owned()
+    }
 
-        match object {
-            Self::Int(_) => {
-                output.push_str(&Self::encode_shallow_data(object));
-            }
-            Self::String(_) => {
-                output.push_str(&Self::encode_shallow_data(object));
-            }
-            Self::BinaryStr(_) => {
-                output.push_str(&Self::encode_shallow_data(object));
-            }
-            Self::List(_) => {
-                output.push_str(&Self::encode_list(object));
-            }
-            Self::Dictionary(_) => {
-                output.push_str(&Self::encode_dict(object));
+    /// Encodes the [`BEncode`] object recursively and returns the exact bencode bytes.
+    /// Note that a tree parsed with [`StringPolicy::Hex`] or [`StringPolicy::LossyUtf8`] holds text in place of the original strings, and that text is what gets encoded
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut output: Vec<u8> = Vec::new();
+        self.encode_into(&mut output)
+            .expect("Writing to a Vec<u8> cannot fail");
+        output
+    }
+
+    /// Encodes the [`BEncode`] object recursively, writing the bencode bytes to `writer`
+    pub fn encode_into<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Self::Int(_) | Self::BigInt(_) | Self::String(_) | Self::BinaryStr(_) => {
+                Self::encode_shallow_data(self, writer)
             }
+            Self::List(_) => Self::encode_list(self, writer),
+            Self::Dictionary(_) => Self::encode_dict(self, writer),
+            Self::Raw(raw) => writer.write_all(raw.as_bytes()),
         }
-
-        output
     }
 
-    /// Internal function to encode non-collection data types - [`BEncode::Int`], [`BEncode::String`] and [`BEncode::BinaryStr`]
-    fn encode_shallow_data(data: &Self) -> String {
+    /// Internal function to encode non-collection data types - [`BEncode::Int`], [`BEncode::BigInt`], [`BEncode::String`] and [`BEncode::BinaryStr`]
+    fn encode_shallow_data<W: Write + ?Sized>(data: &Self, writer: &mut W) -> io::Result<()> {
         match data {
-            Self::Int(num) => format!("i{}e", num),
-            Self::String(string) => format!("{}:{}", string.len(), string),
-            Self::BinaryStr(bin) => unsafe {
-                let bytes = bin.clone();
-                let out: String = String::from_utf8_unchecked(bytes);
-                format!("{}:{}", out.len(), out)
-            },
-            _ => String::from(""),
+            Self::Int(num) => write!(writer, "i{}e", num),
+            Self::BigInt(digits) => write!(writer, "i{}e", digits),
+            Self::String(string) => Self::encode_byte_str(string.as_bytes(), writer),
+            Self::BinaryStr(bin) => Self::encode_byte_str(bin, writer),
+            _ => Ok(()),
         }
     }
 
-    /// Internal function to encode [`BEncode::List`] objects
-    fn encode_list(object: &Self) -> String {
-        let mut output: String = String::new();
+    /// Internal function to encode a bencode ByteString from its raw bytes
+    fn encode_byte_str<W: Write + ?Sized>(bytes: &[u8], writer: &mut W) -> io::Result<()> {
+        write!(writer, "{}:", bytes.len())?;
+        writer.write_all(bytes)
+    }
 
+    /// Internal function to encode [`BEncode::List`] objects
+    fn encode_list<W: Write + ?Sized>(object: &Self, writer: &mut W) -> io::Result<()> {
         if let Self::List(list) = object {
-            output.push('l');
+            writer.write_all(b"l")?;
             for item in list {
-                output.push_str(Self::encode(item).as_str());
+                item.encode_into(writer)?;
             }
-            output.push('e');
+            writer.write_all(b"e")?;
         }
 
-        output
+        Ok(())
     }
 
-    /// Internal function to encode [`BEncode::Dictionary`] objects
-    fn encode_dict(object: &Self) -> String {
-        let mut output: String = String::new();
-
+    /// Internal function to encode [`BEncode::Dictionary`] objects.
+    /// The keys are written in the order of the `BTreeMap`, which compares them as raw bytes as BEP 3 requires
+    fn encode_dict<W: Write + ?Sized>(object: &Self, writer: &mut W) -> io::Result<()> {
         if let Self::Dictionary(dict) = object {
-            output.push('d');
+            writer.write_all(b"d")?;
             for (key, item) in dict {
-                output.push_str(format!("{}:{}", key.len(), key).as_str());
-                output.push_str(Self::encode(item).as_str());
+                Self::encode_byte_str(key, writer)?;
+                item.encode_into(writer)?;
             }
-            output.push('e');
+            writer.write_all(b"e")?;
         }
 
-        output
+        Ok(())
     }
 
-    /// This function is used to push items inside bencode Lists[`BEncode::List`] and Dictionaries[`BEncode::Dictionary`]
-    /// The addition happens in place so it does not return anything
-    /// This will not work for [`BEncode::Int`], [`BEncode::String`] or [`BEncode::BinaryStr`]
-    fn push(&mut self, item: BEncode, key: Option<String>) {
-        match self {
-            Self::Int(_) => {
-                println!("Cannot insert BEncode Object inside Integer!")
-            }
-            Self::String(_) => {
-                println!("Cannot insert BEncode Object inside String!")
-            }
-            Self::BinaryStr(_) => {
-                println!("Cannot insert BEncode Object inside String (Yet)!")
-            }
-            Self::List(value) => {
-                value.push(item);
-            }
-            Self::Dictionary(value) => {
-                if key.is_none() {
-                    println!("No key provided!");
-                    return;
-                }
-                value.insert(key.unwrap(), item);
+    /// Internal function used by the parsers to add items inside bencode Lists[`BEncode::List`] and Dictionaries[`BEncode::Dictionary`].
+    /// The parsers only add to containers, with a key for dictionaries, so anything else is ignored. Use [`BEncode::push`] and [`BEncode::insert`] instead
+    fn add_child(&mut self, item: BEncode, key: Option<Vec<u8>>) {
+        match (self, key) {
+            (Self::List(value), _) => value.push(item),
+            (Self::Dictionary(value), Some(key)) => {
+                value.insert(key, item);
             }
+            _ => (),
         }
     }
+}
 
-    /// Internal function to parse a bencode Integer
-    fn parse_int(bytes: &[u8], mut idx: usize) -> (usize, BEncode) {
-        let mut num_str: String = String::new();
-
-        loop {
-            let ch: String = String::from_utf8(vec![bytes[idx]]).unwrap();
-            idx += 1;
-            match ch.as_str() {
-                c if c.chars().next().unwrap().is_numeric() => {
-                    num_str.push_str(c);
-                }
-                "-" => num_str.push_str(&ch.clone()),
-                "e" => break,
-                "i" => continue,
-                _ => break,
-            }
-        }
-
-        let s = num_str.parse::<isize>();
-        match s {
-            Ok(num) => (idx, BEncode::Int(num)),
-            Err(_err) => {
-                println!("An Error Occured while parsing Int");
-                (idx, BEncode::Int(-1))
-            }
-        }
+impl<'a> ParseNode<'a> for BEncode {
+    fn from_int(num: i64, _span: Range<usize>) -> Self {
+        BEncode::Int(num)
     }
 
-    /// Internal function to parse a bencode ByteString
-    fn parse_str(bytes: &[u8], mut idx: usize, parse_hex: bool) -> (usize, BEncode) {
-        let mut len_str: String = String::new();
+    fn from_big_int(digits: &'a str, _span: Range<usize>) -> Self {
+        BEncode::BigInt(BigInt::from_valid(digits.to_string()))
+    }
 
-        // This loop determines the length of the string
-        loop {
-            let ch: String = String::from_utf8(vec![bytes[idx]]).unwrap();
-            idx += 1;
-            match ch.as_str() {
-                c if c.chars().next().unwrap().is_numeric() => {
-                    len_str.push_str(c);
-                }
-                ":" => break,
-                _ => break,
-            }
+    /// ByteStrings become [`BEncode::String`] or [`BEncode::BinaryStr`] according to the [`StringPolicy`]
+    fn from_bytes(bytes: &'a [u8], _span: Range<usize>, policy: StringPolicy) -> Self {
+        match policy {
+            StringPolicy::Bytes => BEncode::BinaryStr(bytes.to_vec()),
+            StringPolicy::LossyUtf8 => BEncode::String(String::from_utf8_lossy(bytes).into_owned()),
+            StringPolicy::Hex => BEncode::String(hex::encode(bytes)),
+            StringPolicy::Utf8OrBytes => match String::from_utf8(bytes.to_vec()) {
+                Ok(out_str) => BEncode::String(out_str),
+                Err(err) => BEncode::BinaryStr(err.into_bytes()),
+            },
         }
+    }
 
-        let len: usize = len_str
-            .parse::<usize>()
-            .unwrap_or_else(|_err| panic!("Invalid String Length found at column {}", idx));
-
-        let byte_slice: Vec<u8> = bytes[idx..idx + len].to_vec();
-        let out_str = String::from_utf8(byte_slice.clone()).unwrap_or_else(|_e| "".to_string());
+    fn new_list(_start: usize) -> Self {
+        BEncode::List(Vec::new())
+    }
 
-        if out_str.is_empty() {
-            if parse_hex {
-                return (idx + len, BEncode::String(hex::encode(byte_slice)));
-            }
-            return (idx + len, BEncode::BinaryStr(byte_slice.to_vec()));
-        }
+    fn new_dict(_start: usize) -> Self {
+        BEncode::Dictionary(BTreeMap::new())
+    }
 
-        (idx + len, BEncode::String(out_str))
+    fn push(&mut self, item: Self, key: Option<&'a [u8]>) {
+        BEncode::add_child(self, item, key.map(|key| key.to_vec()));
     }
 }
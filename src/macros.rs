@@ -0,0 +1,74 @@
+//! The `bencode!` macro builds [`crate::BEncode`] objects from a JSON-like syntax
+
+/// Builds a [`crate::BEncode`] object.
+/// `key => value` pairs build a Dictionary, `[...]` builds a List, and nested `{...}` and `[...]` build nested containers.
+/// Keys can be anything that converts into `Vec<u8>`, and any other value is converted with `BEncode::from`
+///
+/// ## Example
+/// ```rust
+/// use bencode_parser::{bencode, BEncode};
+///
+/// let length: u64 = 1024;
+/// let torrent: BEncode = bencode! {
+///     "announce" => "http://tracker.example/announce",
+///     "info" => {
+///         "length" => length,
+///         "name" => "spam",
+///         "pieces" => vec![0xffu8; 20],
+///     },
+///     "url-list" => ["http://a.example", "http://b.example"],
+/// };
+/// assert_eq!(torrent["info"]["length"].as_int(), Some(1024));
+/// assert_eq!(bencode!([1, -2, []]).to_bytes(), b"li1ei-2elee");
+/// ```
+#[macro_export]
+macro_rules! bencode {
+    // ======================= DICTIONARY ENTRIES =======================
+    (@dict $dict:ident) => {};
+    (@dict $dict:ident $key:expr => { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+        $dict.insert(::std::convert::Into::into($key), $crate::bencode!({ $($inner)* }));
+        $crate::bencode!(@dict $dict $($($rest)*)?);
+    };
+    (@dict $dict:ident $key:expr => [ $($inner:tt)* ] $(, $($rest:tt)*)?) => {
+        $dict.insert(::std::convert::Into::into($key), $crate::bencode!([ $($inner)* ]));
+        $crate::bencode!(@dict $dict $($($rest)*)?);
+    };
+    (@dict $dict:ident $key:expr => $value:expr $(, $($rest:tt)*)?) => {
+        $dict.insert(::std::convert::Into::into($key), $crate::BEncode::from($value));
+        $crate::bencode!(@dict $dict $($($rest)*)?);
+    };
+
+    // =========================== LIST ITEMS ===========================
+    (@list [$($done:expr),*]) => {
+        $crate::BEncode::List(::std::vec![$($done),*])
+    };
+    (@list [$($done:expr),*] { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@list [$($done,)* $crate::bencode!({ $($inner)* })] $($($rest)*)?)
+    };
+    (@list [$($done:expr),*] [ $($inner:tt)* ] $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@list [$($done,)* $crate::bencode!([ $($inner)* ])] $($($rest)*)?)
+    };
+    (@list [$($done:expr),*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@list [$($done,)* $crate::BEncode::from($value)] $($($rest)*)?)
+    };
+
+    // ============================= VALUES =============================
+    ({ $($inner:tt)* }) => {
+        $crate::bencode!($($inner)*)
+    };
+    ([ $($inner:tt)* ]) => {
+        $crate::bencode!(@list [] $($inner)*)
+    };
+    () => {
+        $crate::BEncode::Dictionary(::std::collections::BTreeMap::new())
+    };
+    ($key:expr => $($rest:tt)*) => {{
+        let mut dict: ::std::collections::BTreeMap<::std::vec::Vec<u8>, $crate::BEncode> =
+            ::std::collections::BTreeMap::new();
+        $crate::bencode!(@dict dict $key => $($rest)*);
+        $crate::BEncode::Dictionary(dict)
+    }};
+    ($value:expr) => {
+        $crate::BEncode::from($value)
+    };
+}
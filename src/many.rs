@@ -0,0 +1,50 @@
+//! The `ParseMany` iterator parses a buffer holding several bencode values one after another
+use crate::parser;
+use crate::{BEncode, DecodeError, Options};
+
+/// An iterator over concatenated bencode values, returned by [`BEncode::parse_many`].
+/// Error offsets are counted from the start of the whole buffer
+pub struct ParseMany<'a> {
+    bytes: &'a [u8],
+    options: Options,
+    idx: usize,
+}
+
+impl<'a> ParseMany<'a> {
+    /// Internal function to create the iterator, use [`BEncode::parse_many`]
+    pub(crate) fn new(bytes: &'a [u8], options: Options) -> Self {
+        Self {
+            bytes,
+            options,
+            idx: 0,
+        }
+    }
+
+    /// Returns the index of the first byte that has not been parsed yet
+    pub fn position(&self) -> usize {
+        self.idx
+    }
+}
+
+impl<'a> Iterator for ParseMany<'a> {
+    type Item = Result<BEncode, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.idx >= self.bytes.len() {
+            return None;
+        }
+
+        match parser::parse_value(&self.bytes[self.idx..], &self.options) {
+            Ok((value, len)) => {
+                self.idx += len;
+                Some(Ok(value))
+            }
+            Err(err) => {
+                let err: DecodeError = err.shifted(self.idx);
+                // Stop after the first error
+                self.idx = self.bytes.len();
+                Some(Err(err))
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Methods that edit Lists and Dictionaries in place, failing with a [`TypeError`] when the value is not the right container
+use crate::{BEncode, TypeError};
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+impl BEncode {
+    /// Returns the items if this is a [`BEncode::List`], so they can be modified
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<BEncode>> {
+        match self {
+            Self::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if this is a [`BEncode::Dictionary`], so they can be modified
+    pub fn as_dict_mut(&mut self) -> Option<&mut BTreeMap<Vec<u8>, BEncode>> {
+        match self {
+            Self::Dictionary(dict) => Some(dict),
+            _ => None,
+        }
+    }
+
+    /// Stores `value` under `key` if this is a [`BEncode::Dictionary`], returning the value it replaced, if any
+    ///
+    /// ## Example
+    /// ```rust
+    /// use bencode_parser::{bencode, BEncode};
+    ///
+    /// let mut torrent: BEncode = bencode! { "comment" => "spam", "info" => { "name" => "spam" } };
+    /// torrent.remove("comment").unwrap();
+    /// torrent.get_mut("info").unwrap().insert("private", 1).unwrap();
+    /// assert_eq!(torrent.to_bytes(), b"d4:infod4:name4:spam7:privatei1eee");
+    /// assert!(torrent["info"]["name"].clone().insert("private", 1).is_err());
+    /// ```
+    pub fn insert<K: Into<Vec<u8>>, V: Into<BEncode>>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> Result<Option<BEncode>, TypeError> {
+        Ok(self.dict_mut()?.insert(key.into(), value.into()))
+    }
+
+    /// Removes the value stored under `key` if this is a [`BEncode::Dictionary`], returning it if it was there
+    pub fn remove<K: AsRef<[u8]>>(&mut self, key: K) -> Result<Option<BEncode>, TypeError> {
+        Ok(self.dict_mut()?.remove(key.as_ref()))
+    }
+
+    /// Returns the entry for `key` if this is a [`BEncode::Dictionary`], to insert or update its value in place
+    pub fn entry<K: Into<Vec<u8>>>(
+        &mut self,
+        key: K,
+    ) -> Result<Entry<'_, Vec<u8>, BEncode>, TypeError> {
+        Ok(self.dict_mut()?.entry(key.into()))
+    }
+
+    /// Adds `value` to the end if this is a [`BEncode::List`]
+    ///
+    /// ## Example
+    /// ```rust
+    /// use bencode_parser::{bencode, BEncode};
+    ///
+    /// let mut torrent: BEncode = bencode! { "announce-list" => [["udp://a.example"]] };
+    /// let tiers: &mut BEncode = torrent.entry("announce-list").unwrap().or_insert(bencode!([]));
+    /// tiers.push(bencode!(["udp://b.example"])).unwrap();
+    /// tiers.retain(|_key, tier| tier[0].as_str() != Some("udp://a.example")).unwrap();
+    /// assert_eq!(torrent.to_bytes(), b"d13:announce-listll15:udp://b.exampleeee");
+    /// ```
+    pub fn push<V: Into<BEncode>>(&mut self, value: V) -> Result<(), TypeError> {
+        self.list_mut()?.push(value.into());
+        Ok(())
+    }
+
+    /// Adds every item of `values` to the end if this is a [`BEncode::List`]
+    pub fn extend<I>(&mut self, values: I) -> Result<(), TypeError>
+    where
+        I: IntoIterator,
+        I::Item: Into<BEncode>,
+    {
+        self.list_mut()?.extend(values.into_iter().map(Into::into));
+        Ok(())
+    }
+
+    /// Keeps only the items of a [`BEncode::List`], or the entries of a [`BEncode::Dictionary`], for which `keep` returns true.
+    /// `keep` is given the key of each entry, or `None` for list items, along with the value
+    pub fn retain<F: FnMut(Option<&[u8]>, &BEncode) -> bool>(
+        &mut self,
+        mut keep: F,
+    ) -> Result<(), TypeError> {
+        match self {
+            Self::List(list) => list.retain(|item| keep(None, item)),
+            Self::Dictionary(dict) => dict.retain(|key, item| keep(Some(key), item)),
+            _ => {
+                return Err(TypeError {
+                    expected: "list or dictionary",
+                    found: self.type_name(),
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// Follows `path` from this value and returns what it leads to.
+    /// Each segment is a key of a Dictionary, or the index of an item of a List written in decimal without leading zeros
+    ///
+    /// ## Example
+    /// ```rust
+    /// use bencode_parser::{bencode, BEncode};
+    ///
+    /// let torrent: BEncode = bencode! { "url-list" => ["a", "b"] };
+    /// assert_eq!(torrent.get_path(&["url-list", "1"]).and_then(BEncode::as_str), Some("b"));
+    /// assert_eq!(torrent.get_path(&["url-list", "01"]), None);
+    /// assert_eq!(torrent.get_path(&["url-list", "+1"]), None);
+    /// ```
+    pub fn get_path<P: AsRef<[u8]>>(&self, path: &[P]) -> Option<&BEncode> {
+        path.iter().try_fold(self, |value, segment| match value {
+            Self::List(list) => list.get(Self::segment_index(segment.as_ref())?),
+            _ => value.get(segment),
+        })
+    }
+
+    /// Follows `path` from this value like [`BEncode::get_path`] and returns a mutable reference to what it leads to
+    pub fn get_path_mut<P: AsRef<[u8]>>(&mut self, path: &[P]) -> Option<&mut BEncode> {
+        path.iter().try_fold(self, |value, segment| match value {
+            Self::List(list) => list.get_mut(Self::segment_index(segment.as_ref())?),
+            _ => value.get_mut(segment),
+        })
+    }
+
+    /// Internal function that reads a path segment as a list index. Only `0` and digits without a leading zero or sign are accepted,
+    /// like JSON Pointer, so that every index has a single spelling
+    pub(crate) fn segment_index(segment: &[u8]) -> Option<usize> {
+        match segment {
+            [b'0'] => Some(0),
+            [b'1'..=b'9', rest @ ..] if rest.iter().all(u8::is_ascii_digit) => {
+                std::str::from_utf8(segment).ok()?.parse::<usize>().ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Internal function that returns the entries of a Dictionary, or a [`TypeError`]
+    fn dict_mut(&mut self) -> Result<&mut BTreeMap<Vec<u8>, BEncode>, TypeError> {
+        let found: &'static str = self.type_name();
+        self.as_dict_mut().ok_or(TypeError {
+            expected: "dictionary",
+            found,
+        })
+    }
+
+    /// Internal function that returns the items of a List, or a [`TypeError`]
+    fn list_mut(&mut self) -> Result<&mut Vec<BEncode>, TypeError> {
+        let found: &'static str = self.type_name();
+        self.as_list_mut().ok_or(TypeError {
+            expected: "list",
+            found,
+        })
+    }
+}
@@ -1,11 +1,137 @@
 //! The `Options` struct contains the configurable options that the parser can take
+use std::collections::BTreeMap;
+
+/// How ByteStrings are turned into [`crate::BEncode`] values
+///
+/// ## Example
+/// ```rust
+/// use bencode_parser::{BEncode, Options, StringPolicy};
+///
+/// let bytes: &[u8] = b"l2:ab2:\xff\xfee";
+/// let options: Options = Options { string_policy: StringPolicy::Hex, ..Options::default() };
+/// let hex: BEncode = BEncode::try_parse(bytes, options).unwrap();
+/// assert_eq!(hex[0].as_str(), Some("6162"));
+/// assert_eq!(hex[1].as_str(), Some("fffe"));
+///
+/// let exact: BEncode = BEncode::try_parse(bytes, Options::default()).unwrap();
+/// assert_eq!(exact[0], BEncode::String("ab".to_string()));
+/// assert_eq!(exact[1], BEncode::BinaryStr(vec![0xff, 0xfe]));
+/// assert_eq!(exact.to_bytes(), bytes);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringPolicy {
+    /// Every ByteString becomes a [`crate::BEncode::BinaryStr`], even if it is valid UTF-8
+    Bytes,
+    /// ByteStrings that are valid UTF-8 become a [`crate::BEncode::String`], the rest become a [`crate::BEncode::BinaryStr`]
+    Utf8OrBytes,
+    /// Every ByteString becomes a [`crate::BEncode::String`], with invalid UTF-8 replaced by `U+FFFD`. This cannot be encoded back exactly
+    LossyUtf8,
+    /// Every ByteString becomes a [`crate::BEncode::String`] holding its bytes as lowercase hex, even if it is valid UTF-8,
+    /// so `2:ab` decodes to `"6162"`. This cannot be encoded back exactly
+    Hex,
+}
+
+/// The options that [`crate::BEncode::try_parse`] and the other entry points decode with
+///
+/// ## Example
+/// ```rust
+/// use bencode_parser::{BEncode, DecodeError, Options};
+///
+/// let strict: Options = Options { strict: true, ..Options::default() };
+/// assert_eq!(BEncode::try_parse(b"i-0e", strict.clone()), Err(DecodeError::NonCanonicalInteger { offset: 0 }));
+/// assert_eq!(BEncode::try_parse(b"i03e", strict.clone()), Err(DecodeError::NonCanonicalInteger { offset: 0 }));
+/// assert_eq!(BEncode::try_parse(b"03:abc", strict.clone()), Err(DecodeError::NonCanonicalLength { offset: 0 }));
+/// assert_eq!(BEncode::try_parse(b"d1:bi1e1:ai2ee", strict.clone()), Err(DecodeError::UnsortedKey { offset: 7 }));
+/// assert_eq!(BEncode::try_parse(b"d1:ai1e1:ai2ee", strict.clone()), Err(DecodeError::DuplicateKey { offset: 7 }));
+/// assert!(BEncode::try_parse(b"d1:ai1e1:ai2ee", Options::default()).is_ok());
+///
+/// let canonical: &[u8] = b"d1:ai-3e1:bl0:2:\xff\xfei0eee";
+/// assert_eq!(BEncode::try_parse(canonical, strict).unwrap().to_bytes(), canonical);
+/// ```
+///
+/// The limits are enforced the same way by [`crate::BEncode::try_parse`], [`crate::Decoder`] and [`crate::BEncode::from_reader`]:
+/// ```rust
+/// use bencode_parser::{BEncode, DecodeError, DecodeStatus, Decoder, Options, ReadError};
+///
+/// fn decode_all(bytes: &[u8], options: Options) -> [Result<BEncode, DecodeError>; 3] {
+///     let parsed: Result<BEncode, DecodeError> = BEncode::try_parse(bytes, options.clone());
+///     let mut decoder: Decoder = Decoder::new(options.clone());
+///     let mut decoded: Result<BEncode, DecodeError> = Err(DecodeError::UnexpectedEof { offset: bytes.len() });
+///     for byte in bytes.chunks(1) {
+///         match decoder.feed(byte) {
+///             DecodeStatus::NeedMore => continue,
+///             DecodeStatus::Value(value, _) => decoded = Ok(value),
+///             DecodeStatus::Error(err) => decoded = Err(err),
+///         }
+///         break;
+///     }
+///     let read: Result<BEncode, DecodeError> = BEncode::from_reader(bytes, options).map_err(|err| match err {
+///         ReadError::Decode(err) => err,
+///         ReadError::Io(err) => panic!("{}", err),
+///     });
+///     [parsed, decoded, read]
+/// }
+///
+/// let depth: Options = Options { max_depth: Some(2), ..Options::default() };
+/// assert!(decode_all(b"lli1eee", depth.clone()).iter().all(Result::is_ok));
+/// assert_eq!(decode_all(b"llli1eeee", depth), [(); 3].map(|_| Err(DecodeError::DepthLimitExceeded { offset: 2 })));
+///
+/// let items: Options = Options { max_items: Some(3), ..Options::default() };
+/// assert!(decode_all(b"d1:ai1e1:bi2ee", items.clone()).iter().all(Result::is_ok));
+/// assert_eq!(decode_all(b"li1ei2ei3ee", items), [(); 3].map(|_| Err(DecodeError::TooManyItems { offset: 7 })));
+///
+/// let strings: Options = Options { max_string_len: Some(4), ..Options::default() };
+/// assert!(decode_all(b"4:spam", strings.clone()).iter().all(Result::is_ok));
+/// assert_eq!(decode_all(b"l5:spamse", strings), [(); 3].map(|_| Err(DecodeError::StringTooLong { offset: 1 })));
+/// ```
+#[derive(Debug, Clone)]
 pub struct Options {
-    /// `parse_hex` tells the parser if the hex data from pieces should be parsed. Default value: `true`
-    pub parse_hex: bool,
+    /// `string_policy` decides how ByteStrings are decoded, unless `key_policies` has an entry for their key. Default value: [`StringPolicy::Utf8OrBytes`]
+    pub string_policy: StringPolicy,
+    /// `key_policies` overrides `string_policy` for ByteStrings stored directly under one of its keys, in any Dictionary.
+    /// For example, mapping `pieces`, `peers`, `nodes` and `id` to [`StringPolicy::Bytes`] keeps hashes and compact addresses binary
+    /// even when they happen to be valid UTF-8. Default value: empty
+    pub key_policies: BTreeMap<Vec<u8>, StringPolicy>,
+    /// `strict` makes the parser reject every encoding that is not canonical according to BEP 3:
+    /// integers with leading zeros or `-0`, length prefixes with leading zeros, and dictionary keys that are unsorted or repeated.
+    /// Input accepted in strict mode re-encodes byte-for-byte as long as every ByteString is decoded with [`StringPolicy::Bytes`] or
+    /// [`StringPolicy::Utf8OrBytes`], since [`StringPolicy::Hex`] and [`StringPolicy::LossyUtf8`] replace strings with text. Default value: `false`
+    pub strict: bool,
+    /// `big_ints` keeps integers that do not fit in an `i64` as their decimal digits in [`crate::BEncode::BigInt`], instead of failing with
+    /// [`crate::DecodeError::IntegerOverflow`]. Default value: `false`
+    pub big_ints: bool,
+    /// `max_depth` is the deepest Lists and Dictionaries may be nested, a top level container has a depth of 1.
+    /// Dropping, cloning, comparing and encoding a value recurse once per level, so `None` is unsafe for untrusted input:
+    /// a few hundred kilobytes of nested lists can overflow the stack and abort the process. Default value: `Some(512)`
+    pub max_depth: Option<usize>,
+    /// `max_string_len` is the longest a ByteString may be, checked against its length prefix before the contents are read. Default value: `None` (no limit)
+    pub max_string_len: Option<usize>,
+    /// `max_items` is how many values, counting containers and their contents but not dictionary keys, a single value may hold. Default value: `None` (no limit)
+    pub max_items: Option<usize>,
+    /// `max_total_bytes` is how many input bytes a single value may span, which bounds the memory used to parse it. Default value: `None` (no limit)
+    pub max_total_bytes: Option<usize>,
 }
 
 impl Options {
-    pub fn default() -> Self {
-        Self { parse_hex: true }
+    /// Returns the policy used for a ByteString stored under `key`, or directly inside a List if `key` is `None`
+    pub fn policy_for(&self, key: Option<&[u8]>) -> StringPolicy {
+        key.and_then(|key| self.key_policies.get(key))
+            .copied()
+            .unwrap_or(self.string_policy)
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            string_policy: StringPolicy::Utf8OrBytes,
+            key_policies: BTreeMap::new(),
+            strict: false,
+            big_ints: false,
+            max_depth: Some(512),
+            max_string_len: None,
+            max_items: None,
+            max_total_bytes: None,
+        }
     }
 }
@@ -0,0 +1,339 @@
+//! The internal parser shared by [`crate::BEncode`], [`crate::BEncodeRef`], [`crate::Spanned`] and [`crate::Visitor`].
+//! It reads the tokens of the input once, keeping track of the open containers, and reports each token to an [`Events`] receiver.
+//! Trees are built by a receiver that works on any type implementing [`ParseNode`]
+use crate::tokenizer::{Token, TokenKind, Tokenizer};
+use crate::{DecodeError, Options, StringPolicy};
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// Internal trait implemented by the tree types the parser can build.
+/// Every node is given the range of input bytes it occupies
+pub(crate) trait ParseNode<'a>: Sized {
+    /// Builds a node from a parsed bencode Integer
+    fn from_int(num: i64, span: Range<usize>) -> Self;
+    /// Builds a node from the decimal digits of an Integer that does not fit in an `i64`
+    fn from_big_int(digits: &'a str, span: Range<usize>) -> Self;
+    /// Builds a node from the raw bytes of a parsed bencode ByteString
+    fn from_bytes(bytes: &'a [u8], span: Range<usize>, policy: StringPolicy) -> Self;
+    /// Builds an empty list node that starts at `start`
+    fn new_list(start: usize) -> Self;
+    /// Builds an empty dictionary node that starts at `start`
+    fn new_dict(start: usize) -> Self;
+    /// Adds `item` to this list, or to this dictionary under `key`
+    fn push(&mut self, item: Self, key: Option<&'a [u8]>);
+    /// Called when this list or dictionary is closed, `end` is the index after its closing `e`
+    fn close(&mut self, _end: usize) {}
+}
+
+/// Internal trait that receives every token of a well formed value, in order
+pub(crate) trait Events<'a> {
+    /// `is_key` is true if the token is a dictionary key
+    fn token(&mut self, token: &Token<'a>, is_key: bool);
+}
+
+/// Parses the first bencode value in `bytes`.
+/// Returns the value along with the index of the first byte after it
+pub(crate) fn parse_value<'a, T: ParseNode<'a>>(
+    bytes: &'a [u8],
+    options: &Options,
+) -> Result<(T, usize), DecodeError> {
+    let mut builder: TreeBuilder<'a, '_, T> = TreeBuilder {
+        options,
+        parents: Vec::new(),
+        dict_keys: Vec::new(),
+        root: None,
+    };
+    let idx: usize = visit_value(bytes, options, &mut builder)?;
+    Ok((builder.root.unwrap(), idx))
+}
+
+/// Internal receiver that builds a tree out of the parsing events
+struct TreeBuilder<'a, 'o, T> {
+    options: &'o Options,
+    // =====================STATE VARIABLES==========================
+    // `parents` holds the containers that are still open, innermost last.
+    // `dict_keys` holds one entry per parent: the key waiting for its value, if any
+    parents: Vec<T>,
+    dict_keys: Vec<Option<&'a [u8]>>,
+    root: Option<T>,
+    // ==============================================================
+}
+
+impl<'a, T: ParseNode<'a>> TreeBuilder<'a, '_, T> {
+    /// Adds a complete value to the innermost open container, or makes it the root
+    fn add(&mut self, value: T) {
+        match self.parents.last_mut() {
+            Some(parent) => parent.push(value, self.dict_keys.last_mut().unwrap().take()),
+            None => self.root = Some(value),
+        }
+    }
+}
+
+impl<'a, T: ParseNode<'a>> Events<'a> for TreeBuilder<'a, '_, T> {
+    fn token(&mut self, token: &Token<'a>, is_key: bool) {
+        let span: Range<usize> = token.span.clone();
+        match token.kind {
+            TokenKind::Bytes(key) if is_key => *self.dict_keys.last_mut().unwrap() = Some(key),
+            TokenKind::Int(num) => self.add(T::from_int(num, span)),
+            TokenKind::BigInt(digits) => self.add(T::from_big_int(digits, span)),
+            TokenKind::Bytes(bytes) => {
+                let key: Option<&[u8]> = self.dict_keys.last().copied().flatten();
+                let policy: StringPolicy = self.options.policy_for(key);
+                self.add(T::from_bytes(bytes, span, policy))
+            }
+            TokenKind::ListStart => {
+                self.parents.push(T::new_list(span.start));
+                self.dict_keys.push(None);
+            }
+            TokenKind::DictStart => {
+                self.parents.push(T::new_dict(span.start));
+                self.dict_keys.push(None);
+            }
+            TokenKind::End => {
+                self.dict_keys.pop();
+                let mut value: T = self.parents.pop().unwrap();
+                value.close(span.end);
+                self.add(value);
+            }
+        }
+    }
+}
+
+/// Internal enum tracking a container that is still open
+enum Frame<'a> {
+    List,
+    /// `last_key` is the previous key, used by strict mode. `awaiting_key` is false while a key waits for its value
+    Dict {
+        last_key: Option<&'a [u8]>,
+        awaiting_key: bool,
+    },
+}
+
+/// Walks the first bencode value in `bytes`, checking that it is well formed and reporting every token to `events`.
+/// Returns the index of the first byte after the value
+pub(crate) fn visit_value<'a, E: Events<'a>>(
+    bytes: &'a [u8],
+    options: &Options,
+    events: &mut E,
+) -> Result<usize, DecodeError> {
+    let mut tokenizer: Tokenizer = Tokenizer::new(bytes, options);
+    let mut frames: Vec<Frame<'a>> = Vec::new();
+    let mut items: usize = 0;
+
+    loop {
+        let token = tokenizer.next().ok_or(DecodeError::UnexpectedEof {
+            offset: bytes.len(),
+        })??;
+        let offset: usize = token.span.start;
+        if let Some(max) = options.max_total_bytes.filter(|&max| token.span.end > max) {
+            return Err(DecodeError::InputTooLarge { offset: max });
+        }
+
+        if let Some(Frame::Dict {
+            last_key,
+            awaiting_key: awaiting_key @ true,
+        }) = frames.last_mut()
+        {
+            // The dictionary is waiting for a key, which is kept as raw bytes
+            match token.kind {
+                TokenKind::Bytes(key) => {
+                    check_key_order(*last_key, key, offset, options)?;
+                    *last_key = Some(key);
+                    *awaiting_key = false;
+                    events.token(&token, true);
+                    continue;
+                }
+                TokenKind::End => (),
+                _ => return Err(DecodeError::NonStringKey { offset }),
+            }
+        }
+
+        if token.kind != TokenKind::End {
+            items += 1;
+            let opens: bool = matches!(token.kind, TokenKind::ListStart | TokenKind::DictStart);
+            let depth: usize = frames.len() + opens as usize;
+            check_limits(depth, items, offset, options)?;
+        }
+
+        match token.kind {
+            TokenKind::Int(_) | TokenKind::BigInt(_) | TokenKind::Bytes(_) => {
+                events.token(&token, false)
+            }
+            TokenKind::ListStart => {
+                frames.push(Frame::List);
+                events.token(&token, false);
+                continue;
+            }
+            TokenKind::DictStart => {
+                frames.push(Frame::Dict {
+                    last_key: None,
+                    awaiting_key: true,
+                });
+                events.token(&token, false);
+                continue;
+            }
+            TokenKind::End => match frames.pop() {
+                Some(Frame::Dict {
+                    awaiting_key: false,
+                    ..
+                })
+                | None => {
+                    // Either a key is waiting for its value, or there is no container to close
+                    return Err(DecodeError::UnexpectedByte { offset, byte: b'e' });
+                }
+                Some(_) => events.token(&token, false),
+            },
+        }
+
+        // A value is complete
+        match frames.last_mut() {
+            Some(Frame::Dict { awaiting_key, .. }) => *awaiting_key = true,
+            Some(Frame::List) => (),
+            None => return Ok(tokenizer.position()),
+        }
+    }
+}
+
+/// Reads the token starting at `bytes[idx]`, which must exist.
+/// Returns the index of the first byte after the token along with its kind
+pub(crate) fn read_token<'a>(
+    bytes: &'a [u8],
+    idx: usize,
+    options: &Options,
+) -> Result<(usize, TokenKind<'a>), DecodeError> {
+    match bytes[idx] {
+        b'i' => parse_int(bytes, idx, options),
+        b'0'..=b'9' => {
+            parse_bytes(bytes, idx, options).map(|(end, bytes)| (end, TokenKind::Bytes(bytes)))
+        }
+        b'l' => Ok((idx + 1, TokenKind::ListStart)),
+        b'd' => Ok((idx + 1, TokenKind::DictStart)),
+        b'e' => Ok((idx + 1, TokenKind::End)),
+        byte => Err(DecodeError::UnexpectedByte { offset: idx, byte }),
+    }
+}
+
+/// Checks the `max_depth` and `max_items` limits when a value starts at `offset`.
+/// `depth` is the number of containers open once it has started and `items` the number of values so far, including it
+pub(crate) fn check_limits(
+    depth: usize,
+    items: usize,
+    offset: usize,
+    options: &Options,
+) -> Result<(), DecodeError> {
+    if options.max_depth.is_some_and(|max| depth > max) {
+        return Err(DecodeError::DepthLimitExceeded { offset });
+    }
+    if options.max_items.is_some_and(|max| items > max) {
+        return Err(DecodeError::TooManyItems { offset });
+    }
+    Ok(())
+}
+
+/// In strict mode, checks that `key` sorts after `last_key`, the previous key of the same dictionary.
+/// `offset` is the position of `key` in the input
+pub(crate) fn check_key_order(
+    last_key: Option<&[u8]>,
+    key: &[u8],
+    offset: usize,
+    options: &Options,
+) -> Result<(), DecodeError> {
+    if !options.strict {
+        return Ok(());
+    }
+
+    // Keys must appear exactly once, sorted by their raw bytes
+    match last_key.map(|last| last.cmp(key)) {
+        Some(Ordering::Equal) => Err(DecodeError::DuplicateKey { offset }),
+        Some(Ordering::Greater) => Err(DecodeError::UnsortedKey { offset }),
+        _ => Ok(()),
+    }
+}
+
+/// Parses a bencode Integer, `idx` must point at the leading `i`.
+/// Returns the index of the first byte after the closing `e` along with a [`TokenKind::Int`],
+/// or a [`TokenKind::BigInt`] if the number does not fit in an `i64` and [`Options::big_ints`] is set
+pub(crate) fn parse_int<'a>(
+    bytes: &'a [u8],
+    idx: usize,
+    options: &Options,
+) -> Result<(usize, TokenKind<'a>), DecodeError> {
+    let digits_start: usize = idx + 1;
+    let end: usize = bytes[digits_start..]
+        .iter()
+        .position(|&b| !matches!(b, b'-' | b'0'..=b'9'))
+        .map(|pos| digits_start + pos)
+        .ok_or(DecodeError::UnexpectedEof {
+            offset: bytes.len(),
+        })?;
+    if bytes[end] != b'e' {
+        return Err(DecodeError::InvalidInteger { offset: idx });
+    }
+
+    // Only ASCII digits and `-` were accepted above
+    let num_str: &'a str = std::str::from_utf8(&bytes[digits_start..end]).unwrap();
+    let digits: &str = num_str.strip_prefix('-').unwrap_or(num_str);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(DecodeError::InvalidInteger { offset: idx });
+    }
+
+    // BEP 3 forbids leading zeros and negative zero
+    if options.strict && digits.starts_with('0') && num_str != "0" {
+        return Err(DecodeError::NonCanonicalInteger { offset: idx });
+    }
+
+    match num_str.parse::<i64>() {
+        Ok(num) => Ok((end + 1, TokenKind::Int(num))),
+        Err(_err) if options.big_ints => Ok((end + 1, TokenKind::BigInt(num_str))),
+        Err(_err) => Err(DecodeError::IntegerOverflow { offset: idx }),
+    }
+}
+
+/// Parses the raw bytes of a bencode ByteString, `idx` must point at the first digit of the length prefix.
+/// Returns the index of the first byte after the string along with its contents
+pub(crate) fn parse_bytes<'a>(
+    bytes: &'a [u8],
+    mut idx: usize,
+    options: &Options,
+) -> Result<(usize, &'a [u8]), DecodeError> {
+    let len_start: usize = idx;
+    let mut len: usize = 0;
+
+    // This loop determines the length of the string
+    loop {
+        let byte: u8 = *bytes
+            .get(idx)
+            .ok_or(DecodeError::UnexpectedEof { offset: idx })?;
+        idx += 1;
+        match byte {
+            b'0'..=b'9' => {
+                len = len
+                    .checked_mul(10)
+                    .and_then(|len| len.checked_add((byte - b'0') as usize))
+                    .ok_or(DecodeError::InvalidLength { offset: len_start })?;
+            }
+            b':' => break,
+            _ => return Err(DecodeError::InvalidLength { offset: len_start }),
+        }
+    }
+
+    if idx == len_start + 1 {
+        // The length prefix had no digits
+        return Err(DecodeError::InvalidLength { offset: len_start });
+    }
+    if options.strict && bytes[len_start] == b'0' && idx > len_start + 2 {
+        return Err(DecodeError::NonCanonicalLength { offset: len_start });
+    }
+    if options.max_string_len.is_some_and(|max| len > max) {
+        return Err(DecodeError::StringTooLong { offset: len_start });
+    }
+
+    let end: usize = idx
+        .checked_add(len)
+        .filter(|&end| end <= bytes.len())
+        .ok_or(DecodeError::UnexpectedEof {
+            offset: bytes.len(),
+        })?;
+
+    Ok((end, &bytes[idx..end]))
+}
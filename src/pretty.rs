@@ -0,0 +1,181 @@
+//! The `PrettyPrinter` struct renders a [`BEncode`] object as indented, human readable text
+use crate::BEncode;
+use std::fmt::{self, Write};
+
+/// How a [`PrettyPrinter`] renders binary strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryFormat {
+    /// Every byte as two lowercase hex digits, like `<hex 0a1b>`
+    Hex,
+    /// Standard base64 with padding, like `<base64 Chs=>`
+    Base64,
+    /// The length and at most this many leading bytes as hex, like `<20 bytes 0a1b...>`
+    Preview(usize),
+}
+
+/// An indented printer for [`BEncode`] objects.
+/// Strings are quoted and escaped, so `"5"` cannot be mistaken for the Integer `5`, and binary strings are rendered with [`BinaryFormat`].
+/// The `Display` implementation of [`BEncode`] uses the default printer
+///
+/// ## Example
+/// ```rust
+/// use bencode_parser::{BEncode, BinaryFormat, PrettyPrinter};
+///
+/// let value: BEncode = BEncode::List(vec![BEncode::Int(5), BEncode::String("5".to_string()), BEncode::BinaryStr(vec![0xff, 0x00])]);
+/// let printer: PrettyPrinter = PrettyPrinter { binary: BinaryFormat::Hex, ..PrettyPrinter::default() };
+/// assert_eq!(printer.print(&value), "[\n  5,\n  \"5\",\n  <hex ff00>\n]");
+///
+/// let compact: PrettyPrinter = PrettyPrinter { compact: true, ..printer };
+/// assert_eq!(compact.print(&value), "[5, \"5\", <hex ff00>]");
+///
+/// let base64: PrettyPrinter = PrettyPrinter { binary: BinaryFormat::Base64, ..PrettyPrinter::default() };
+/// assert_eq!(base64.print(&BEncode::BinaryStr(b"hello".to_vec())), "<base64 aGVsbG8=>");
+/// ```
+pub struct PrettyPrinter {
+    /// `indent` is the number of spaces added for every level of nesting. Default value: `2`
+    pub indent: usize,
+    /// `binary` is how every [`BEncode::BinaryStr`] is rendered, even one holding valid UTF-8, along with dictionary keys that are not valid UTF-8.
+    /// Default value: [`BinaryFormat::Preview`] of 20 bytes
+    pub binary: BinaryFormat,
+    /// `compact` renders the whole value on a single line, like `[1, "a"]`, ignoring `indent`. Default value: `false`
+    pub compact: bool,
+}
+
+impl Default for PrettyPrinter {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            binary: BinaryFormat::Preview(20),
+            compact: false,
+        }
+    }
+}
+
+impl PrettyPrinter {
+    /// Renders `value` and returns the text
+    pub fn print(&self, value: &BEncode) -> String {
+        let mut output: String = String::new();
+        self.write(value, &mut output)
+            .expect("Writing to a String cannot fail");
+        output
+    }
+
+    /// Renders `value`, writing the text to `writer`
+    pub fn write<W: Write + ?Sized>(&self, value: &BEncode, writer: &mut W) -> fmt::Result {
+        self.write_value(value, 0, writer)
+    }
+
+    /// Internal function that renders `value` nested `depth` levels deep
+    fn write_value<W: Write + ?Sized>(
+        &self,
+        value: &BEncode,
+        depth: usize,
+        writer: &mut W,
+    ) -> fmt::Result {
+        match value {
+            BEncode::Int(num) => write!(writer, "{}", num),
+            BEncode::BigInt(digits) => write!(writer, "{}", digits),
+            BEncode::String(string) => write!(writer, "{:?}", string),
+            BEncode::BinaryStr(bin) => self.write_binary(bin, writer),
+            BEncode::Raw(raw) => {
+                write!(writer, "raw ")?;
+                self.write_binary(raw.as_bytes(), writer)
+            }
+            BEncode::List(list) if list.is_empty() => write!(writer, "[]"),
+            BEncode::List(list) => {
+                self.write_open('[', writer)?;
+                for (idx, item) in list.iter().enumerate() {
+                    self.write_indent(depth + 1, writer)?;
+                    self.write_value(item, depth + 1, writer)?;
+                    self.write_separator(idx + 1 < list.len(), writer)?;
+                }
+                self.write_indent(depth, writer)?;
+                write!(writer, "]")
+            }
+            BEncode::Dictionary(dict) if dict.is_empty() => write!(writer, "{{}}"),
+            BEncode::Dictionary(dict) => {
+                self.write_open('{', writer)?;
+                for (idx, (key, item)) in dict.iter().enumerate() {
+                    self.write_indent(depth + 1, writer)?;
+                    match std::str::from_utf8(key) {
+                        Ok(key) => write!(writer, "{:?}", key)?,
+                        Err(_err) => self.write_binary(key, writer)?,
+                    }
+                    write!(writer, ": ")?;
+                    self.write_value(item, depth + 1, writer)?;
+                    self.write_separator(idx + 1 < dict.len(), writer)?;
+                }
+                self.write_indent(depth, writer)?;
+                write!(writer, "}}")
+            }
+        }
+    }
+
+    /// Internal function that renders binary data according to [`PrettyPrinter::binary`]
+    fn write_binary<W: Write + ?Sized>(&self, bytes: &[u8], writer: &mut W) -> fmt::Result {
+        match self.binary {
+            BinaryFormat::Hex => write!(writer, "<hex {}>", hex::encode(bytes)),
+            BinaryFormat::Base64 => write!(writer, "<base64 {}>", encode_base64(bytes)),
+            BinaryFormat::Preview(max) if bytes.len() > max => write!(
+                writer,
+                "<{} bytes {}...>",
+                bytes.len(),
+                hex::encode(&bytes[..max])
+            ),
+            BinaryFormat::Preview(_) => {
+                write!(writer, "<{} bytes {}>", bytes.len(), hex::encode(bytes))
+            }
+        }
+    }
+
+    /// Internal function that opens a List or Dictionary
+    fn write_open<W: Write + ?Sized>(&self, bracket: char, writer: &mut W) -> fmt::Result {
+        match self.compact {
+            true => write!(writer, "{}", bracket),
+            false => writeln!(writer, "{}", bracket),
+        }
+    }
+
+    /// Internal function that writes the leading spaces of a line nested `depth` levels deep
+    fn write_indent<W: Write + ?Sized>(&self, depth: usize, writer: &mut W) -> fmt::Result {
+        match self.compact {
+            true => Ok(()),
+            false => write!(writer, "{:width$}", "", width = depth * self.indent),
+        }
+    }
+
+    /// Internal function that ends the line of a List item or Dictionary entry
+    fn write_separator<W: Write + ?Sized>(&self, more: bool, writer: &mut W) -> fmt::Result {
+        match (more, self.compact) {
+            (true, true) => write!(writer, ", "),
+            (false, true) => Ok(()),
+            (true, false) => writeln!(writer, ","),
+            (false, false) => writeln!(writer),
+        }
+    }
+}
+
+/// Internal function that encodes `bytes` as standard base64 with padding
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output: String = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group: u32 = chunk.iter().enumerate().fold(0, |group, (idx, &byte)| {
+            group | (byte as u32) << (16 - 8 * idx)
+        });
+        // A chunk of n bytes fills n + 1 characters, the rest is padding
+        for idx in 0..4 {
+            match idx <= chunk.len() {
+                true => output.push(ALPHABET[(group >> (18 - 6 * idx) & 0x3f) as usize] as char),
+                false => output.push('='),
+            }
+        }
+    }
+    output
+}
+
+impl fmt::Display for BEncode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        PrettyPrinter::default().write(self, f)
+    }
+}
@@ -0,0 +1,204 @@
+//! Lookups of nested values: JSON Pointer style paths with [`BEncode::pointer`], and the [`Query`] syntax with wildcards
+use crate::{BEncode, QueryError};
+use std::borrow::Cow;
+use std::str::FromStr;
+
+impl BEncode {
+    /// Follows a JSON Pointer (RFC 6901) such as `/info/files/3/path/0` and returns the value it leads to.
+    /// `~1` in a segment stands for `/` and `~0` for `~`. The empty pointer refers to this value itself,
+    /// and a pointer that does not start with `/` leads nowhere
+    ///
+    /// ## Example
+    /// ```rust
+    /// use bencode_parser::{bencode, BEncode};
+    ///
+    /// let torrent: BEncode = bencode! { "info" => { "files" => [{ "path" => ["a", "b.txt"] }] } };
+    /// assert_eq!(torrent.pointer("/info/files/0/path/1").and_then(BEncode::as_str), Some("b.txt"));
+    /// assert_eq!(torrent.pointer("/info/files/1"), None);
+    /// assert_eq!(torrent.pointer("/info/files/00"), None);
+    /// assert_eq!(torrent.pointer(""), Some(&torrent));
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Option<&BEncode> {
+        self.get_path(&Self::pointer_segments(pointer)?)
+    }
+
+    /// Follows a JSON Pointer like [`BEncode::pointer`] and returns a mutable reference to the value it leads to
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut BEncode> {
+        self.get_path_mut(&Self::pointer_segments(pointer)?)
+    }
+
+    /// Runs `query` against this value and returns the values it matches, see [`Query`] for the syntax
+    ///
+    /// ## Example
+    /// ```rust
+    /// use bencode_parser::{bencode, BEncode};
+    ///
+    /// let torrent: BEncode = bencode! {
+    ///     "info" => { "files" => [{ "length" => 10 }, { "length" => 32 }, { "path" => ["c"] }] },
+    /// };
+    /// let total: i64 = torrent.query("info.files[*].length").unwrap().filter_map(BEncode::as_int).sum();
+    /// assert_eq!(total, 42);
+    /// ```
+    pub fn query(&self, query: &str) -> Result<QueryMatches<'static, '_>, QueryError> {
+        let query: Query = query.parse()?;
+        Ok(QueryMatches {
+            steps: Cow::Owned(query.steps),
+            stack: vec![(self, 0)],
+        })
+    }
+
+    /// Internal function that splits a JSON Pointer into its unescaped segments
+    fn pointer_segments(pointer: &str) -> Option<Vec<String>> {
+        if pointer.is_empty() {
+            return Some(Vec::new());
+        }
+        let segments: Vec<String> = pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .collect();
+        Some(segments)
+    }
+}
+
+/// Internal step of a [`Query`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    /// The value stored under a key of a Dictionary
+    Key(Vec<u8>),
+    /// The item at an index of a List
+    Index(usize),
+    /// Every item of a List, or every value of a Dictionary in key order
+    Wildcard,
+}
+
+/// A parsed query that selects values nested in a [`BEncode`] object.
+///
+/// A query is a sequence of steps applied from the root value:
+/// - `name` selects the value stored under the key `name` of a Dictionary, and is written `.name` after another step.
+///   Keys run up to the next `.` or `[`
+/// - `[3]` selects the item at index 3 of a List. Indexes are written without leading zeros or a sign
+/// - `[*]` selects every item of a List, and `*` as a key selects every value of a Dictionary
+///
+/// Steps that do not apply to a value, such as a key on a List, match nothing. The empty query matches the root value
+///
+/// ## Example
+/// ```rust
+/// use bencode_parser::{bencode, BEncode, Query};
+///
+/// let torrent: BEncode = bencode! {
+///     "announce-list" => [["udp://a.example", "udp://b.example"], ["udp://c.example"]],
+/// };
+/// let query: Query = "announce-list[*][0]".parse().unwrap();
+/// let first: Vec<&str> = query.matches(&torrent).filter_map(BEncode::as_str).collect();
+/// assert_eq!(first, ["udp://a.example", "udp://c.example"]);
+/// assert!(Query::parse("announce-list[x]").is_err());
+/// assert!(Query::parse("announce-list[01]").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+impl Query {
+    /// Parses a query, see the type documentation for the syntax
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        let mut steps: Vec<Step> = Vec::new();
+        let mut idx: usize = 0;
+        // Whether a key must come next, which is the case at the start and after a dot
+        let mut want_key: bool = true;
+        while idx < query.len() || (want_key && !steps.is_empty()) {
+            let rest: &str = &query[idx..];
+            if rest.starts_with('[') {
+                if want_key && !steps.is_empty() {
+                    return Err(QueryError::EmptyKey { offset: idx });
+                }
+                let end: usize = rest
+                    .find(']')
+                    .ok_or(QueryError::UnclosedBracket { offset: idx })?;
+                steps.push(match &rest[1..end] {
+                    "*" => Step::Wildcard,
+                    index => Step::Index(
+                        BEncode::segment_index(index.as_bytes())
+                            .ok_or(QueryError::InvalidIndex { offset: idx + 1 })?,
+                    ),
+                });
+                idx += end + 1;
+                want_key = false;
+            } else if want_key {
+                let end: usize = rest.find(['.', '[']).unwrap_or(rest.len());
+                if end == 0 {
+                    return Err(QueryError::EmptyKey { offset: idx });
+                }
+                steps.push(match &rest[..end] {
+                    "*" => Step::Wildcard,
+                    key => Step::Key(key.as_bytes().to_vec()),
+                });
+                idx += end;
+                want_key = false;
+            } else if rest.starts_with('.') {
+                idx += 1;
+                want_key = true;
+            } else {
+                let ch: char = rest.chars().next().expect("The query is not finished");
+                return Err(QueryError::UnexpectedChar { offset: idx, ch });
+            }
+        }
+        Ok(Self { steps })
+    }
+
+    /// Returns an iterator over the values of `root` that this query matches, in the order they appear in the value
+    pub fn matches<'q, 'v>(&'q self, root: &'v BEncode) -> QueryMatches<'q, 'v> {
+        QueryMatches {
+            steps: Cow::Borrowed(&self.steps),
+            stack: vec![(root, 0)],
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(query: &str) -> Result<Self, QueryError> {
+        Self::parse(query)
+    }
+}
+
+/// Iterator over the values matched by a [`Query`], created by [`Query::matches`] and [`BEncode::query`]
+#[derive(Debug)]
+pub struct QueryMatches<'q, 'v> {
+    /// Borrowed from a [`Query`], or owned when created by [`BEncode::query`]
+    steps: Cow<'q, [Step]>,
+    // =====STATE VARIABLES=====
+    /// Values still to visit, with the number of steps already applied to reach them. The top is visited first
+    stack: Vec<(&'v BEncode, usize)>,
+}
+
+impl<'v> Iterator for QueryMatches<'_, 'v> {
+    type Item = &'v BEncode;
+
+    fn next(&mut self) -> Option<&'v BEncode> {
+        let steps: &[Step] = &self.steps;
+        while let Some((value, done)) = self.stack.pop() {
+            let Some(step) = steps.get(done) else {
+                return Some(value);
+            };
+            match (step, value) {
+                (Step::Key(key), _) => self
+                    .stack
+                    .extend(value.get(key).map(|child| (child, done + 1))),
+                (Step::Index(index), BEncode::List(list)) => self
+                    .stack
+                    .extend(list.get(*index).map(|child| (child, done + 1))),
+                (Step::Wildcard, BEncode::List(list)) => self
+                    .stack
+                    .extend(list.iter().rev().map(|child| (child, done + 1))),
+                (Step::Wildcard, BEncode::Dictionary(dict)) => self
+                    .stack
+                    .extend(dict.values().rev().map(|child| (child, done + 1))),
+                _ => {}
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,151 @@
+//! The `RawBEncode` struct holds the exact bytes of a single valid bencode value, so it can be copied verbatim into new output
+use crate::parser::{self, Events};
+use crate::{BEncode, DecodeError, LazyValue, Options, Spanned, StringPolicy, Token};
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// The exact bytes of one valid bencode value, either borrowed from the input or owned.
+/// Put it in a [`BEncode::Raw`] to embed it unchanged when encoding, for example to edit the top level fields of a torrent
+/// while copying the `info` dictionary byte-for-byte so its hash cannot change.
+/// The first time a raw value is compared or hashed it is decoded, and the decoded value is kept for later comparisons.
+/// Because of that, a raw value may only be nested as deep as the default [`Options::max_depth`] allows
+///
+/// ## Example
+/// ```rust
+/// use bencode_parser::{BEncode, LazyDocument, Options, RawBEncode};
+/// use std::collections::BTreeMap;
+///
+/// let bytes: &[u8] = b"d8:announce3:old4:infod4:name4:spamee";
+/// let doc: LazyDocument = LazyDocument::new(bytes, Options::default()).unwrap();
+/// let info: RawBEncode = doc.get("info").unwrap().to_raw().unwrap();
+///
+/// let mut dict: BTreeMap<Vec<u8>, BEncode> = BTreeMap::new();
+/// dict.insert(b"announce".to_vec(), BEncode::String("new".to_string()));
+/// dict.insert(b"info".to_vec(), BEncode::Raw(info.into_owned()));
+/// assert_eq!(BEncode::Dictionary(dict).to_bytes(), b"d8:announce3:new4:infod4:name4:spamee");
+/// ```
+#[derive(Clone)]
+pub struct RawBEncode<'a> {
+    bytes: Cow<'a, [u8]>,
+    /// The value returned by `decode_exact`, decoded on first use
+    exact: OnceLock<Box<BEncode>>,
+}
+
+impl<'a> RawBEncode<'a> {
+    /// Checks that `bytes` holds exactly one well formed bencode value. Integers of any size are accepted,
+    /// but the limits of the default [`Options`] apply, so values nested too deep fail with [`DecodeError::DepthLimitExceeded`]
+    pub fn new<B: Into<Cow<'a, [u8]>>>(bytes: B) -> Result<Self, DecodeError> {
+        let bytes: Cow<'a, [u8]> = bytes.into();
+        let idx: usize = parser::visit_value(&bytes, &Self::exact_options(), &mut NoEvents)?;
+        if idx < bytes.len() {
+            return Err(DecodeError::TrailingData { offset: idx });
+        }
+        Ok(Self::from_cow(bytes))
+    }
+
+    /// Internal function that wraps bytes that have already been checked, without a decoded value yet
+    fn from_cow(bytes: Cow<'a, [u8]>) -> Self {
+        Self {
+            bytes,
+            exact: OnceLock::new(),
+        }
+    }
+
+    /// Returns the bencode bytes of the value
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Copies the bytes if they are borrowed, so the value no longer borrows from the input
+    pub fn into_owned(self) -> RawBEncode<'static> {
+        RawBEncode {
+            bytes: Cow::Owned(self.bytes.into_owned()),
+            exact: self.exact,
+        }
+    }
+
+    /// Decodes the value into a [`BEncode`] object
+    pub fn decode(&self, options: Options) -> Result<BEncode, DecodeError> {
+        BEncode::try_parse(&self.bytes, options)
+    }
+
+    /// Internal function that decodes the value keeping every ByteString exactly and integers of any size.
+    /// If a dictionary repeats a key, the last value is kept. The value is decoded once and then reused
+    pub(crate) fn decode_exact(&self) -> &BEncode {
+        self.exact.get_or_init(|| {
+            let value: BEncode = self
+                .decode(Self::exact_options())
+                .expect("The value was checked with the same options when it was created");
+            Box::new(value)
+        })
+    }
+
+    /// Internal function that returns the options raw values are checked and decoded with
+    fn exact_options() -> Options {
+        Options {
+            big_ints: true,
+            string_policy: StringPolicy::Utf8OrBytes,
+            ..Options::default()
+        }
+    }
+}
+
+impl fmt::Debug for RawBEncode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RawBEncode").field(&self.bytes).finish()
+    }
+}
+
+/// Raw values are equal when their bytes are, compare them as [`BEncode::Raw`] to match equivalent encodings
+impl PartialEq for RawBEncode<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for RawBEncode<'_> {}
+
+impl BEncode {
+    /// Encodes this object and returns its bytes as a [`RawBEncode`], checked like [`RawBEncode::new`]
+    pub fn to_raw(&self) -> Result<RawBEncode<'static>, DecodeError> {
+        RawBEncode::new(self.to_bytes())
+    }
+}
+
+impl<T> Spanned<T> {
+    /// Returns the exact input bytes this value was parsed from as a [`RawBEncode`]. `input` must be the buffer that was parsed,
+    /// the bytes are checked again so that another buffer fails instead of producing an invalid raw value
+    ///
+    /// ## Example
+    /// ```rust
+    /// use bencode_parser::{BEncode, Options, Spanned, SpannedBEncode};
+    ///
+    /// let input: &[u8] = b"li1ee";
+    /// let spanned: Spanned<SpannedBEncode> = BEncode::parse_spanned(input, Options::default()).unwrap();
+    /// assert_eq!(spanned.to_raw(input).unwrap().as_bytes(), b"li1ee");
+    /// assert!(spanned.to_raw(b"xyzwv").is_err());
+    /// assert!(spanned.to_raw(b"li1").is_err());
+    /// assert_eq!(spanned.raw(b"li1"), None);
+    /// ```
+    pub fn to_raw<'a>(&self, input: &'a [u8]) -> Result<RawBEncode<'a>, DecodeError> {
+        let bytes: &'a [u8] = self.raw(input).ok_or(DecodeError::UnexpectedEof {
+            offset: input.len(),
+        })?;
+        RawBEncode::new(bytes)
+    }
+}
+
+impl<'a> LazyValue<'_, 'a> {
+    /// Returns the exact input bytes of this value as a [`RawBEncode`], checked like [`RawBEncode::new`]
+    pub fn to_raw(&self) -> Result<RawBEncode<'a>, DecodeError> {
+        RawBEncode::new(self.raw())
+    }
+}
+
+/// Internal receiver that ignores every token, used to only check the input
+struct NoEvents;
+
+impl<'a> Events<'a> for NoEvents {
+    fn token(&mut self, _token: &Token<'a>, _is_key: bool) {}
+}
@@ -0,0 +1,140 @@
+//! Parsing a single bencode value from a [`std::io::Read`] stream
+use crate::parser;
+use crate::{BEncode, DecodeError, Options, ReadError};
+use std::io::{self, ErrorKind, Read};
+
+impl BEncode {
+    /// This function reads exactly one bencode value from `reader` and returns the parsed [`BEncode`] object.
+    /// The reader is left positioned on the first byte after the value, so it can be called repeatedly on a stream of concatenated values.
+    /// Bytes are read one at a time outside of ByteStrings, so wrap unbuffered readers like sockets or files in a [`std::io::BufReader`]
+    ///
+    /// ## Example
+    /// ```rust
+    /// use bencode_parser::{BEncode, DecodeError, Options, ReadError};
+    /// use std::io::Cursor;
+    ///
+    /// let mut stream: Cursor<&[u8]> = Cursor::new(b"i1e4:spam");
+    /// assert_eq!(BEncode::from_reader(&mut stream, Options::default()).unwrap(), BEncode::Int(1));
+    /// assert_eq!(BEncode::from_reader(&mut stream, Options::default()).unwrap().as_str(), Some("spam"));
+    ///
+    /// let options: Options = Options { max_total_bytes: Some(1), ..Options::default() };
+    /// let result: Result<BEncode, ReadError> = BEncode::from_reader(Cursor::new(b"1:a"), options);
+    /// assert!(matches!(result, Err(ReadError::Decode(DecodeError::InputTooLarge { .. }))));
+    /// ```
+    pub fn from_reader<R: Read>(mut reader: R, options: Options) -> Result<Self, ReadError> {
+        let bytes: Vec<u8> = Self::read_value_bytes(&mut reader, &options)?;
+        let (root, _) = parser::parse_value(&bytes, &options)?;
+        Ok(root)
+    }
+
+    /// Internal function that copies the bytes of one bencode value from `reader` without reading past its end.
+    /// It only follows the structure of the value, if the input is malformed or breaks a limit of `options` it stops early and the parser reports the error.
+    /// `max_total_bytes` is checked here, since the bytes have to be buffered before they can be parsed
+    fn read_value_bytes<R: Read>(reader: &mut R, options: &Options) -> Result<Vec<u8>, ReadError> {
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut depth: usize = 0;
+        let max_total_bytes: usize = options.max_total_bytes.unwrap_or(usize::MAX);
+
+        loop {
+            Self::check_size(&bytes, max_total_bytes)?;
+            let byte: u8 = match Self::read_byte(reader)? {
+                Some(byte) => byte,
+                None => return Ok(bytes),
+            };
+            bytes.push(byte);
+
+            match byte {
+                // Integer
+                b'i' => loop {
+                    Self::check_size(&bytes, max_total_bytes)?;
+                    match Self::read_byte(reader)? {
+                        Some(b'e') => {
+                            bytes.push(b'e');
+                            break;
+                        }
+                        Some(byte @ (b'-' | b'0'..=b'9')) => bytes.push(byte),
+                        Some(byte) => {
+                            bytes.push(byte);
+                            return Ok(bytes);
+                        }
+                        None => return Ok(bytes),
+                    }
+                },
+                // String
+                b'0'..=b'9' => {
+                    let mut len: u64 = (byte - b'0') as u64;
+                    loop {
+                        Self::check_size(&bytes, max_total_bytes)?;
+                        match Self::read_byte(reader)? {
+                            Some(b':') => {
+                                bytes.push(b':');
+                                break;
+                            }
+                            Some(byte @ b'0'..=b'9') => {
+                                bytes.push(byte);
+                                len = len.saturating_mul(10).saturating_add((byte - b'0') as u64);
+                            }
+                            Some(byte) => {
+                                bytes.push(byte);
+                                return Ok(bytes);
+                            }
+                            None => return Ok(bytes),
+                        }
+                    }
+                    Self::check_size(&bytes, max_total_bytes)?;
+                    if options.max_string_len.is_some_and(|max| len > max as u64) {
+                        return Ok(bytes);
+                    }
+                    // `take` grows the buffer as data arrives, so a bogus length cannot allocate up front.
+                    // Reading one byte past `max_total_bytes` is enough to know the value is too large
+                    let budget: u64 = (max_total_bytes - bytes.len()).saturating_add(1) as u64;
+                    let read: u64 = reader
+                        .by_ref()
+                        .take(len.min(budget))
+                        .read_to_end(&mut bytes)? as u64;
+                    Self::check_size(&bytes, max_total_bytes)?;
+                    if read < len {
+                        return Ok(bytes);
+                    }
+                }
+                // List or Dictionary
+                b'l' | b'd' => {
+                    depth += 1;
+                    if options.max_depth.is_some_and(|max| depth > max) {
+                        return Ok(bytes);
+                    }
+                    continue;
+                }
+                b'e' if depth > 0 => depth -= 1,
+                _ => return Ok(bytes),
+            }
+
+            if depth == 0 {
+                return Ok(bytes);
+            }
+        }
+    }
+
+    /// Internal function that fails once more than `max_total_bytes` bytes have been buffered
+    fn check_size(bytes: &[u8], max_total_bytes: usize) -> Result<(), DecodeError> {
+        if bytes.len() > max_total_bytes {
+            return Err(DecodeError::InputTooLarge {
+                offset: max_total_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    /// Internal function that reads a single byte, returning `None` at the end of the stream
+    fn read_byte<R: Read>(reader: &mut R) -> io::Result<Option<u8>> {
+        let mut buf: [u8; 1] = [0];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(buf[0])),
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
@@ -0,0 +1,333 @@
+//! Lenient parsing that keeps going after problems in the input and reports them as [`Diagnostic`]s
+use crate::parser::{self, ParseNode};
+use crate::{BEncode, DecodeError, Options, StringPolicy, TokenKind};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// How serious a [`Diagnostic`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Nothing was lost, but the input is not what it should be: a non-canonical encoding or unsorted keys found in strict mode, or trailing data
+    Warning,
+    /// Part of the input was skipped or dropped, a repeated dictionary key overwrote an earlier value, or a container the input left open was closed
+    Error,
+}
+
+/// A problem found by [`BEncode::parse_lenient`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How serious the problem is
+    pub severity: Severity,
+    /// What went wrong and where. [`BEncode::try_parse`] would have failed with this error, except for a repeated dictionary key
+    /// outside strict mode, which it accepts by keeping the last value
+    pub error: DecodeError,
+}
+
+impl Diagnostic {
+    /// Returns the byte offset in the input at which the problem was detected
+    pub fn offset(&self) -> usize {
+        self.error.offset()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning: {}", self.error),
+            Severity::Error => write!(f, "error: {}", self.error),
+        }
+    }
+}
+
+impl BEncode {
+    /// This function parses `bytes` like [`BEncode::try_parse`], but keeps going after problems instead of stopping at the first one.
+    /// Malformed integers and stray bytes are skipped, a dictionary key that is not a ByteString is dropped along with its value,
+    /// and containers left open at the end of the input are closed. In strict mode non-canonical encodings are accepted with a warning.
+    /// Exceeding a limit in [`Options`] still stops parsing, keeping what was parsed so far.
+    /// Returns the best-effort value, or `None` if nothing could be recovered, along with every problem found
+    ///
+    /// ## Example
+    /// ```rust
+    /// use bencode_parser::{BEncode, Options, Severity};
+    ///
+    /// let (value, diagnostics) = BEncode::parse_lenient(b"d1:ai1x2e1:bi5e", Options::default());
+    /// assert_eq!(value.unwrap().get("b").map(|b| b.to_bytes()), Some(b"i5e".to_vec()));
+    /// assert_eq!(diagnostics.len(), 2);
+    /// assert!(diagnostics.iter().all(|diag| diag.severity == Severity::Error));
+    ///
+    /// let (value, diagnostics) = BEncode::parse_lenient(b"d1:ai1e1:ai2ee", Options::default());
+    /// assert_eq!(value.unwrap().get("a").and_then(BEncode::as_int), Some(2));
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!(diagnostics[0].severity, Severity::Error);
+    /// ```
+    pub fn parse_lenient(bytes: &[u8], options: Options) -> (Option<Self>, Vec<Diagnostic>) {
+        let lax: Options = Options {
+            strict: false,
+            key_policies: BTreeMap::new(),
+            ..options
+        };
+        let mut recovery: Recovery = Recovery {
+            bytes,
+            options: &options,
+            lax: &lax,
+            frames: Vec::new(),
+            items: 0,
+            diagnostics: Vec::new(),
+        };
+        let root: Option<BEncode> = recovery.run();
+        (root, recovery.diagnostics)
+    }
+}
+
+/// Internal enum tracking what the innermost open Dictionary expects next
+#[derive(Clone, Copy)]
+enum Slot<'a> {
+    /// A key, or the `e` closing the dictionary. Lists always stay in this slot
+    Key,
+    /// The value of `key`
+    Value(&'a [u8]),
+    /// The value of a key that was dropped, which is dropped too
+    Orphan,
+}
+
+/// Internal struct holding a container that is still open
+struct Frame<'a> {
+    node: BEncode,
+    slot: Slot<'a>,
+    /// The previous key of a Dictionary, used by strict mode
+    last_key: Option<&'a [u8]>,
+    start: usize,
+}
+
+/// Internal state of [`BEncode::parse_lenient`]
+struct Recovery<'a, 'o> {
+    bytes: &'a [u8],
+    options: &'o Options,
+    // `lax` is `options` without strict mode, used to re-read tokens that are only non-canonical
+    lax: &'o Options,
+    // =====================STATE VARIABLES==========================
+    // `frames` holds the containers that are still open, innermost last.
+    // `items` is the number of values started so far, for `Options::max_items`
+    frames: Vec<Frame<'a>>,
+    items: usize,
+    diagnostics: Vec<Diagnostic>,
+    // ==============================================================
+}
+
+impl<'a> Recovery<'a, '_> {
+    /// Internal function that parses the first value in the input, returning the root once it is complete
+    fn run(&mut self) -> Option<BEncode> {
+        let mut idx: usize = 0;
+        loop {
+            if idx >= self.bytes.len() {
+                self.report(Severity::Error, DecodeError::UnexpectedEof { offset: idx });
+                return self.abandon();
+            }
+
+            let (end, kind) = match self.read_token(idx) {
+                Ok(token) => token,
+                Err(err) => match self.skip(err, idx) {
+                    Some(next) => {
+                        idx = next;
+                        continue;
+                    }
+                    None => return self.abandon(),
+                },
+            };
+            if let Some(max) = self.options.max_total_bytes.filter(|&max| end > max) {
+                self.report(Severity::Error, DecodeError::InputTooLarge { offset: max });
+                return self.abandon();
+            }
+
+            let frame: Option<&Frame> = self.frames.last();
+            let awaiting_key: bool = matches!(
+                frame,
+                Some(Frame {
+                    node: BEncode::Dictionary(_),
+                    slot: Slot::Key,
+                    ..
+                })
+            );
+            let value_key: Option<&[u8]> = match frame.map(|frame| frame.slot) {
+                Some(Slot::Value(key)) => Some(key),
+                _ => None,
+            };
+
+            let is_key: bool = awaiting_key && matches!(kind, TokenKind::Bytes(_));
+            if kind != TokenKind::End && !is_key {
+                self.items += 1;
+                let opens: bool = matches!(kind, TokenKind::ListStart | TokenKind::DictStart);
+                let depth: usize = self.frames.len() + opens as usize;
+                if let Err(err) = parser::check_limits(depth, self.items, idx, self.options) {
+                    self.report(Severity::Error, err);
+                    return self.abandon();
+                }
+            }
+
+            let root: Option<BEncode> = match kind {
+                TokenKind::Bytes(key) if is_key => {
+                    let frame: &mut Frame = self.frames.last_mut().unwrap();
+                    let duplicate: bool =
+                        matches!(&frame.node, BEncode::Dictionary(dict) if dict.contains_key(key));
+                    let order: Result<(), DecodeError> =
+                        parser::check_key_order(frame.last_key, key, idx, self.options);
+                    frame.last_key = Some(key);
+                    frame.slot = Slot::Value(key);
+                    if duplicate {
+                        // The value stored earlier under the same key is overwritten, in strict mode or not
+                        self.report(Severity::Error, DecodeError::DuplicateKey { offset: idx });
+                    } else if let Err(err) = order {
+                        self.report(Severity::Warning, err);
+                    }
+                    None
+                }
+                TokenKind::Int(num) => self.add(BEncode::from_int(num, idx..end), idx),
+                TokenKind::BigInt(digits) => self.add(BEncode::from_big_int(digits, idx..end), idx),
+                TokenKind::Bytes(bytes) => {
+                    let policy: StringPolicy = self.options.policy_for(value_key);
+                    self.add(BEncode::from_bytes(bytes, idx..end, policy), idx)
+                }
+                TokenKind::ListStart | TokenKind::DictStart => {
+                    let node: BEncode = match kind {
+                        TokenKind::ListStart => BEncode::new_list(idx),
+                        _ => BEncode::new_dict(idx),
+                    };
+                    self.frames.push(Frame {
+                        node,
+                        slot: Slot::Key,
+                        last_key: None,
+                        start: idx,
+                    });
+                    None
+                }
+                TokenKind::End => match self.frames.pop() {
+                    Some(frame) => {
+                        if let Slot::Value(_) = frame.slot {
+                            // The key waiting for its value is dropped
+                            let err: DecodeError = DecodeError::UnexpectedByte {
+                                offset: idx,
+                                byte: b'e',
+                            };
+                            self.report(Severity::Error, err);
+                        }
+                        self.add(frame.node, frame.start)
+                    }
+                    None => {
+                        let err: DecodeError = DecodeError::UnexpectedByte {
+                            offset: idx,
+                            byte: b'e',
+                        };
+                        self.report(Severity::Error, err);
+                        None
+                    }
+                },
+            };
+
+            if let Some(root) = root {
+                if end < self.bytes.len() {
+                    self.report(Severity::Warning, DecodeError::TrailingData { offset: end });
+                }
+                return Some(root);
+            }
+            idx = end;
+        }
+    }
+
+    /// Internal function that reads the token at `idx`. In strict mode a non-canonical token is reported and read again without strict mode
+    fn read_token(&mut self, idx: usize) -> Result<(usize, TokenKind<'a>), DecodeError> {
+        match parser::read_token(self.bytes, idx, self.options) {
+            Err(
+                err @ (DecodeError::NonCanonicalInteger { .. }
+                | DecodeError::NonCanonicalLength { .. }),
+            ) => {
+                self.report(Severity::Warning, err);
+                parser::read_token(self.bytes, idx, self.lax)
+            }
+            res => res,
+        }
+    }
+
+    /// Internal function that reports a malformed token at `idx` and returns the index to continue from, or `None` if parsing must stop
+    fn skip(&mut self, err: DecodeError, idx: usize) -> Option<usize> {
+        let next: Option<usize> = match err {
+            // Skip everything up to and including the `e` that probably ends the integer, which counts as a dropped value
+            DecodeError::InvalidInteger { .. } | DecodeError::IntegerOverflow { .. } => {
+                self.drop_value();
+                Some(
+                    self.bytes[idx..]
+                        .iter()
+                        .position(|&b| b == b'e')
+                        .map_or(self.bytes.len(), |pos| idx + pos + 1),
+                )
+            }
+            // Skip the digits of the length prefix
+            DecodeError::InvalidLength { .. } => Some(
+                idx + self.bytes[idx..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_digit())
+                    .count()
+                    .max(1),
+            ),
+            // Skip up to the next byte that can start a token
+            DecodeError::UnexpectedByte { .. } => Some(
+                self.bytes[idx + 1..]
+                    .iter()
+                    .position(|&b| matches!(b, b'i' | b'l' | b'd' | b'e' | b'0'..=b'9'))
+                    .map_or(self.bytes.len(), |pos| idx + 1 + pos),
+            ),
+            // The input ended inside the token, or a limit was exceeded
+            _ => None,
+        };
+        self.report(Severity::Error, err);
+        next
+    }
+
+    /// Internal function that adds a complete value, which started at `start`, to the innermost open container.
+    /// Returns the value if it is the root
+    fn add(&mut self, value: BEncode, start: usize) -> Option<BEncode> {
+        let frame: &mut Frame = match self.frames.last_mut() {
+            Some(frame) => frame,
+            None => return Some(value),
+        };
+
+        match (&frame.node, frame.slot) {
+            (BEncode::List(_), _) => frame.node.add_child(value, None),
+            (_, Slot::Value(key)) => {
+                frame.node.add_child(value, Some(key.to_vec()));
+                frame.slot = Slot::Key;
+            }
+            (_, Slot::Key) => {
+                // The value was used as a key, so it is dropped along with the value that follows it
+                frame.slot = Slot::Orphan;
+                self.report(Severity::Error, DecodeError::NonStringKey { offset: start });
+            }
+            (_, Slot::Orphan) => frame.slot = Slot::Key,
+        }
+        None
+    }
+
+    /// Internal function that moves the innermost open Dictionary past a value that was skipped.
+    /// A skipped value drops its key, and a skipped key drops its value
+    fn drop_value(&mut self) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.slot = match (&frame.node, frame.slot) {
+                (BEncode::Dictionary(_), Slot::Key) => Slot::Orphan,
+                _ => Slot::Key,
+            };
+        }
+    }
+
+    /// Internal function that closes every open container and returns the root
+    fn abandon(&mut self) -> Option<BEncode> {
+        let mut root: Option<BEncode> = None;
+        while let Some(frame) = self.frames.pop() {
+            root = self.add(frame.node, frame.start);
+        }
+        root
+    }
+
+    /// Internal function that records a problem
+    fn report(&mut self, severity: Severity, error: DecodeError) {
+        self.diagnostics.push(Diagnostic { severity, error });
+    }
+}
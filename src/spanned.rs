@@ -0,0 +1,140 @@
+//! The `Spanned` struct pairs a parsed value with the range of input bytes it was parsed from
+use crate::parser::{self, ParseNode};
+use crate::{BEncode, BigInt, DecodeError, Options, StringPolicy};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// A value along with the range of input bytes it occupied.
+/// For containers the span covers everything from the opening `l`/`d` to the closing `e`
+#[derive(Debug)]
+pub struct Spanned<T> {
+    /// The parsed value
+    pub value: T,
+    /// The bytes of the input the value occupied
+    pub span: Range<usize>,
+}
+
+impl<T> Spanned<T> {
+    /// Returns the exact input bytes this value was parsed from. `input` must be the buffer that was parsed,
+    /// `None` is returned if it is too short to hold the span
+    pub fn raw<'a>(&self, input: &'a [u8]) -> Option<&'a [u8]> {
+        input.get(self.span.clone())
+    }
+}
+
+/// The span-tracked BEncode Object returned by [`BEncode::parse_spanned`].
+/// It mirrors [`BEncode`], but every child of a List or Dictionary is wrapped in a [`Spanned`]
+#[derive(Debug)]
+pub enum SpannedBEncode {
+    /// The `Int` variant holds the integers parsed from bencode
+    Int(i64),
+    /// The `BigInt` variant holds the decimal digits of integers that do not fit in an `i64`
+    BigInt(BigInt),
+    /// The `String` variant holds parsed bencode ByteStrings that have valid UTF-8 characters
+    String(String),
+    /// The `List` variant holds parsed bencode Lists along with the span of every item
+    List(Vec<Spanned<SpannedBEncode>>),
+    /// The `Dictionary` variant holds parsed bencode Dictionaries along with the span of every value
+    Dictionary(BTreeMap<Vec<u8>, Spanned<SpannedBEncode>>),
+    /// The `BinaryStr` variant holds parsed bencode ByteStrings that do not have valid UTF-8 characters
+    BinaryStr(Vec<u8>),
+}
+
+impl SpannedBEncode {
+    /// Returns the value stored under `key`, along with its span, if this is a [`SpannedBEncode::Dictionary`]
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<&Spanned<SpannedBEncode>> {
+        match self {
+            Self::Dictionary(dict) => dict.get(key.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Drops the spans and returns the equivalent [`BEncode`] object
+    pub fn into_bencode(self) -> BEncode {
+        match self {
+            Self::Int(num) => BEncode::Int(num),
+            Self::BigInt(digits) => BEncode::BigInt(digits),
+            Self::String(string) => BEncode::String(string),
+            Self::BinaryStr(bin) => BEncode::BinaryStr(bin),
+            Self::List(list) => BEncode::List(
+                list.into_iter()
+                    .map(|item| item.value.into_bencode())
+                    .collect(),
+            ),
+            Self::Dictionary(dict) => BEncode::Dictionary(
+                dict.into_iter()
+                    .map(|(key, item)| (key, item.value.into_bencode()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl BEncode {
+    /// This function parses `bytes` like [`BEncode::try_parse`], but records the range of input bytes every node occupied.
+    /// This allows hashing the exact original bytes of a sub-document, like the `info` dictionary of a torrent
+    pub fn parse_spanned(
+        bytes: &[u8],
+        options: Options,
+    ) -> Result<Spanned<SpannedBEncode>, DecodeError> {
+        let (root, idx) = parser::parse_value(bytes, &options)?;
+        if idx < bytes.len() {
+            return Err(DecodeError::TrailingData { offset: idx });
+        }
+        Ok(root)
+    }
+}
+
+impl<'a> ParseNode<'a> for Spanned<SpannedBEncode> {
+    fn from_int(num: i64, span: Range<usize>) -> Self {
+        Spanned {
+            value: SpannedBEncode::Int(num),
+            span,
+        }
+    }
+
+    fn from_big_int(digits: &'a str, span: Range<usize>) -> Self {
+        Spanned {
+            value: SpannedBEncode::BigInt(BigInt::from_valid(digits.to_string())),
+            span,
+        }
+    }
+
+    /// ByteStrings are decoded exactly like they are for [`BEncode`]
+    fn from_bytes(bytes: &'a [u8], span: Range<usize>, policy: StringPolicy) -> Self {
+        let value: SpannedBEncode = match BEncode::from_bytes(bytes, span.clone(), policy) {
+            BEncode::String(string) => SpannedBEncode::String(string),
+            BEncode::BinaryStr(bin) => SpannedBEncode::BinaryStr(bin),
+            _ => unreachable!("ByteStrings always decode to a String or BinaryStr"),
+        };
+        Spanned { value, span }
+    }
+
+    fn new_list(start: usize) -> Self {
+        Spanned {
+            value: SpannedBEncode::List(Vec::new()),
+            span: start..start,
+        }
+    }
+
+    fn new_dict(start: usize) -> Self {
+        Spanned {
+            value: SpannedBEncode::Dictionary(BTreeMap::new()),
+            span: start..start,
+        }
+    }
+
+    fn push(&mut self, item: Self, key: Option<&'a [u8]>) {
+        match (&mut self.value, key) {
+            (SpannedBEncode::List(list), _) => list.push(item),
+            (SpannedBEncode::Dictionary(dict), Some(key)) => {
+                dict.insert(key.to_vec(), item);
+            }
+            _ => (),
+        }
+    }
+
+    fn close(&mut self, end: usize) {
+        self.span.end = end;
+    }
+}
@@ -0,0 +1,95 @@
+//! The `Tokenizer` splits bencode input into Integers, ByteStrings and container delimiters, without checking how they nest
+use crate::parser;
+use crate::{DecodeError, Options};
+use std::ops::Range;
+
+/// The kind of a [`Token`], along with its parsed contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenKind<'a> {
+    /// An Integer (`i...e`)
+    Int(i64),
+    /// An Integer that does not fit in an `i64`, holding its decimal digits and sign. Only produced when [`Options::big_ints`] is set
+    BigInt(&'a str),
+    /// A ByteString (`<len>:...`), holding its contents without the length prefix
+    Bytes(&'a [u8]),
+    /// The `l` that opens a List
+    ListStart,
+    /// The `d` that opens a Dictionary
+    DictStart,
+    /// The `e` that closes a List or Dictionary
+    End,
+}
+
+/// A single token along with the range of input bytes it occupies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    /// What the token is, along with its parsed contents
+    pub kind: TokenKind<'a>,
+    /// The bytes of the input the token occupies, including the `i`/`e` of Integers and the length prefix of ByteStrings
+    pub span: Range<usize>,
+}
+
+/// An iterator over the tokens of a bencode input.
+/// It only checks that each token is well formed, not that lists and dictionaries nest correctly or that keys are ByteStrings,
+/// which makes it suitable for syntax highlighting and custom fast paths. It yields `Err` once and then stops if a token is malformed
+///
+/// ## Example
+/// ```rust
+/// use bencode_parser::{Options, TokenKind, Tokenizer};
+///
+/// let options: Options = Options::default();
+/// let kinds: Vec<TokenKind> = Tokenizer::new(b"li42e4:spame", &options)
+///     .map(|token| token.unwrap().kind)
+///     .collect();
+/// assert_eq!(
+///     kinds,
+///     vec![TokenKind::ListStart, TokenKind::Int(42), TokenKind::Bytes(b"spam"), TokenKind::End]
+/// );
+/// ```
+pub struct Tokenizer<'a, 'o> {
+    bytes: &'a [u8],
+    options: &'o Options,
+    idx: usize,
+}
+
+impl<'a, 'o> Tokenizer<'a, 'o> {
+    /// Creates a tokenizer over `bytes`, checking Integers and length prefixes according to `options`
+    pub fn new(bytes: &'a [u8], options: &'o Options) -> Self {
+        Self {
+            bytes,
+            options,
+            idx: 0,
+        }
+    }
+
+    /// Returns the index of the first byte that has not been tokenized yet
+    pub fn position(&self) -> usize {
+        self.idx
+    }
+}
+
+impl<'a, 'o> Iterator for Tokenizer<'a, 'o> {
+    type Item = Result<Token<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start: usize = self.idx;
+        if start >= self.bytes.len() {
+            return None;
+        }
+
+        match parser::read_token(self.bytes, start, self.options) {
+            Ok((end, kind)) => {
+                self.idx = end;
+                Some(Ok(Token {
+                    kind,
+                    span: start..end,
+                }))
+            }
+            Err(err) => {
+                // Stop after the first error
+                self.idx = self.bytes.len();
+                Some(Err(err))
+            }
+        }
+    }
+}
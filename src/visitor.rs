@@ -0,0 +1,56 @@
+//! The `Visitor` trait receives parsing events one by one, so bencode can be scanned without building a [`BEncode`] tree
+use crate::parser::{self, Events};
+use crate::{BEncode, DecodeError, Options, Token, TokenKind};
+
+/// A receiver of parsing events, used with [`BEncode::visit`].
+/// Every method does nothing by default, so only the events of interest need to be implemented.
+/// ByteStrings and keys borrow from the input, so they can be kept without copying
+pub trait Visitor<'a> {
+    /// Called for every Integer
+    fn on_int(&mut self, _value: i64) {}
+    /// Called for every Integer that does not fit in an `i64`, with its decimal digits and sign. Only used when [`Options::big_ints`] is set
+    fn on_big_int(&mut self, _digits: &'a str) {}
+    /// Called for every ByteString that is not a dictionary key
+    fn on_bytes(&mut self, _value: &'a [u8]) {}
+    /// Called when a List is opened
+    fn on_list_start(&mut self) {}
+    /// Called when a Dictionary is opened
+    fn on_dict_start(&mut self) {}
+    /// Called for every dictionary key, before the events of its value
+    fn on_key(&mut self, _key: &'a [u8]) {}
+    /// Called when the innermost open List or Dictionary is closed
+    fn on_end(&mut self) {}
+}
+
+impl BEncode {
+    /// This function walks the bencode value in `bytes`, calling `visitor` for every event instead of building a [`BEncode`] object.
+    /// The input is validated exactly like [`BEncode::try_parse`], and events already delivered are not taken back if an error is found later
+    pub fn visit<'a, V: Visitor<'a>>(
+        bytes: &'a [u8],
+        options: Options,
+        visitor: &mut V,
+    ) -> Result<(), DecodeError> {
+        let idx: usize = parser::visit_value(bytes, &options, &mut VisitorEvents(visitor))?;
+        if idx < bytes.len() {
+            return Err(DecodeError::TrailingData { offset: idx });
+        }
+        Ok(())
+    }
+}
+
+/// Internal adapter that turns the tokens reported by the parser into [`Visitor`] calls
+struct VisitorEvents<'v, V>(&'v mut V);
+
+impl<'a, V: Visitor<'a>> Events<'a> for VisitorEvents<'_, V> {
+    fn token(&mut self, token: &Token<'a>, is_key: bool) {
+        match token.kind {
+            TokenKind::Bytes(key) if is_key => self.0.on_key(key),
+            TokenKind::Int(num) => self.0.on_int(num),
+            TokenKind::BigInt(digits) => self.0.on_big_int(digits),
+            TokenKind::Bytes(bytes) => self.0.on_bytes(bytes),
+            TokenKind::ListStart => self.0.on_list_start(),
+            TokenKind::DictStart => self.0.on_dict_start(),
+            TokenKind::End => self.0.on_end(),
+        }
+    }
+}